@@ -0,0 +1,127 @@
+// Broadphase benchmark - Compares the old nested bullet/enemy loop against the spatial hash broadphase,
+// without opening a window. Run with cargo run --release --example broadphase
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use std::time::{Duration, Instant};
+
+#[allow(dead_code)]
+#[path = "../src/hitbox.rs"]
+mod hitbox;
+#[path = "../src/spatial.rs"]
+mod spatial;
+
+use hitbox::Hitbox;
+use spatial::{SpatialHash, GRID_CELL_SIZE};
+
+// Number of enemies (and the same number of bullets) to benchmark
+const COUNTS: [usize; 5] = [100, 500, 1000, 5000, 10000];
+
+// Space per enemy, about as crowded as a busy screen, so the play area grows with the count
+const AREA_PER_ENEMY: f32 = 100.0 * 100.0;
+
+// Each count is timed this many times and averaged (the first round warms up the grid's memory)
+const ROUNDS: u32 = 5;
+
+// Body Struct - An enemy or bullet, boxes like the game's bullets
+struct Body {
+    position: Vec2,
+    size: f32,
+    hitbox: Hitbox,
+}
+
+impl Body {
+    fn collides_with(&self, other: &Body) -> bool {
+        self.hitbox.overlaps(
+            self.position,
+            self.size,
+            &other.hitbox,
+            other.position,
+            other.size,
+        )
+    }
+
+    fn rect(&self) -> Rect {
+        self.hitbox.bounds(self.position, self.size)
+    }
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>14} {:>14} {:>8}",
+        "enemies", "nested loop", "spatial hash", "hits"
+    );
+
+    for count in COUNTS {
+        let rng = RandGenerator::new();
+        rng.srand(count as u64);
+        let side = (AREA_PER_ENEMY * count as f32).sqrt();
+        let enemies: Vec<Body> = (0..count)
+            .map(|_| random_body(&rng, side, rng.gen_range(16.0, 64.0)))
+            .collect();
+        let bullets: Vec<Body> = (0..count).map(|_| random_body(&rng, side, 32.0)).collect();
+
+        let (nested_time, nested_hits) = time(|| nested_hits(&enemies, &bullets));
+        let mut grid = SpatialHash::new(GRID_CELL_SIZE);
+        let (grid_time, grid_hits) = time(|| grid_hits(&mut grid, &enemies, &bullets));
+        assert_eq!(
+            nested_hits, grid_hits,
+            "broadphase found different hits to the nested loop"
+        );
+
+        println!(
+            "{:>8} {:>14} {:>14} {:>8}",
+            count,
+            format!("{:.2?}", nested_time),
+            format!("{:.2?}", grid_time),
+            grid_hits
+        );
+    }
+}
+
+fn random_body(rng: &RandGenerator, side: f32, size: f32) -> Body {
+    Body {
+        position: vec2(rng.gen_range(0.0, side), rng.gen_range(0.0, side)),
+        size,
+        hitbox: Hitbox::Aabb { w: 1.0, h: 1.0 },
+    }
+}
+
+fn time(mut f: impl FnMut() -> usize) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..ROUNDS {
+        hits = f();
+    }
+    (start.elapsed() / ROUNDS, hits)
+}
+
+fn nested_hits(enemies: &[Body], bullets: &[Body]) -> usize {
+    let mut hits = 0;
+    for enemy in enemies {
+        for bullet in bullets {
+            if bullet.collides_with(enemy) {
+                hits += 1;
+            }
+        }
+    }
+    hits
+}
+
+// Like World::step, the grid is cleared and rebuilt each time but keeps its memory
+fn grid_hits(grid: &mut SpatialHash, enemies: &[Body], bullets: &[Body]) -> usize {
+    grid.clear();
+    for (index, enemy) in enemies.iter().enumerate() {
+        grid.insert(index, enemy.rect());
+    }
+
+    let mut hits = 0;
+    let mut nearby = vec![];
+    for bullet in bullets {
+        grid.query(bullet.rect(), &mut nearby);
+        hits += nearby
+            .iter()
+            .filter(|&&index| bullet.collides_with(&enemies[index]))
+            .count();
+    }
+    hits
+}
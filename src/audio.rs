@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use macroquad::audio::{play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+use macroquad::prelude::*;
+
+use crate::events::{EventListener, GameEvent};
+use crate::profile::Settings;
+
+// Sfx Enum - Every sound effect the mixer can play
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Laser,
+    Explosion,
+    PowerUp,
+}
+
+impl Sfx {
+    // Limit function - How many of this effect may play at once, and roughly how long one lasts in seconds.
+    // Macroquad can't tell when a sound finishes, so an instance counts as playing for that long
+    fn limit(&self) -> (usize, f64) {
+        match self {
+            Sfx::Laser => (3, 0.25),
+            Sfx::Explosion => (4, 0.8),
+            Sfx::PowerUp => (2, 0.5),
+        }
+    }
+}
+
+// Effect Struct - A sound effect and when its playing instances were started
+struct Effect {
+    sound: Sound,
+    started: Vec<f64>,
+}
+
+// Seconds a crossfade between music tracks takes
+const CROSSFADE_TIME: f32 = 1.5;
+// Music volume while paused
+const DUCKED_VOLUME: f32 = 0.3;
+// Level at which the gameplay music reaches full intensity
+const MAX_INTENSITY_LEVEL: u32 = 10;
+// Mixer channel of each music track
+const GAMEPLAY_CHANNEL: usize = 0;
+const MENU_CHANNEL: usize = 1;
+const INTENSE_CHANNEL: usize = 2;
+
+// Music State Enum - What the music should be doing for the current scene
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MusicState {
+    Silent,
+    Menu,
+    Gameplay,
+    GameOver,
+}
+
+// Music Tracks Struct - The theme plays during the game with the intense layer fading in over it, the menu
+// track in the menus and the sting once at game over
+pub struct MusicTracks {
+    pub theme: Sound,
+    pub menu: Sound,
+    pub intense: Sound,
+    pub game_over: Sound,
+}
+
+// Channel Struct - A looping music track and how loud it is in the crossfade
+struct Channel {
+    sound: Sound,
+    volume: f32,
+    playing: bool,
+}
+
+// Mixer Struct - Owns every sound and plays them through the master, music and sfx buses
+pub struct Mixer {
+    // Music, faded between by update
+    channels: Vec<Channel>,
+    game_over_sting: Sound,
+    music_state: MusicState,
+    // 0.0 at level 1 up to 1.0 at MAX_INTENSITY_LEVEL
+    intensity: f32,
+    ducked: bool,
+
+    effects: HashMap<Sfx, Effect>,
+
+    // Bus volumes from the settings, 0.0 to 1.0
+    master_volume: f32,
+    music_volume: f32,
+    sfx_volume: f32,
+    muted: bool,
+}
+
+impl Mixer {
+    pub fn new(
+        music: MusicTracks,
+        laser: Sound,
+        explosion: Sound,
+        power_up: Sound,
+        settings: &Settings,
+    ) -> Mixer {
+        let channel = |sound| Channel {
+            sound,
+            volume: 0.0,
+            playing: false,
+        };
+        let effect = |sound| Effect {
+            sound,
+            started: vec![],
+        };
+
+        // In the order of the channel constants
+        let channels = vec![
+            channel(music.theme),
+            channel(music.menu),
+            channel(music.intense),
+        ];
+
+        let effects = HashMap::from([
+            (Sfx::Laser, effect(laser)),
+            (Sfx::Explosion, effect(explosion)),
+            (Sfx::PowerUp, effect(power_up)),
+        ]);
+
+        let mut mixer = Mixer {
+            channels,
+            game_over_sting: music.game_over,
+            music_state: MusicState::Silent,
+            intensity: 0.0,
+            ducked: false,
+            effects,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            muted: false,
+        };
+        mixer.apply_settings(settings);
+        mixer
+    }
+
+    // Apply settings function - Takes the bus volumes and mute from the settings, the music follows on the next update
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.master_volume = settings.master_volume;
+        self.music_volume = settings.music_volume;
+        self.sfx_volume = settings.sfx_volume;
+        self.muted = settings.muted;
+    }
+
+    // Set music function - Crossfades to the music for a state, game over also plays the sting and leaving
+    // it stops the sting
+    pub fn set_music(&mut self, state: MusicState) {
+        if state == self.music_state {
+            return;
+        }
+        if self.music_state == MusicState::GameOver {
+            stop_sound(&self.game_over_sting);
+        }
+        self.music_state = state;
+
+        if state == MusicState::GameOver {
+            play_sound(
+                &self.game_over_sting,
+                PlaySoundParams {
+                    looped: false,
+                    volume: self.music_gain(),
+                },
+            );
+        }
+    }
+
+    // Set level function - The gameplay music builds up as the level goes up
+    pub fn set_level(&mut self, level: u32) {
+        self.intensity =
+            (level.saturating_sub(1) as f32 / (MAX_INTENSITY_LEVEL - 1) as f32).min(1.0);
+    }
+
+    // Set ducked function - Turns the music down while paused
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    // Update function - Call once per frame, fades each music channel towards its volume for the current state.
+    // Channels start from silence when they fade in and stop once they have faded out
+    pub fn update(&mut self, delta_time: f32) {
+        let targets = self.music_targets();
+        let fade = delta_time / CROSSFADE_TIME;
+        let gain = self.music_gain();
+
+        for (channel, target) in self.channels.iter_mut().zip(targets) {
+            match target {
+                Some(target) => {
+                    if !channel.playing {
+                        play_sound(
+                            &channel.sound,
+                            PlaySoundParams {
+                                looped: true,
+                                volume: 0.0,
+                            },
+                        );
+                        channel.playing = true;
+                    }
+                    channel.volume = move_towards(channel.volume, target, fade);
+                }
+                None => {
+                    channel.volume = move_towards(channel.volume, 0.0, fade);
+                    if channel.playing && channel.volume == 0.0 {
+                        stop_sound(&channel.sound);
+                        channel.playing = false;
+                    }
+                }
+            }
+
+            if channel.playing {
+                set_sound_volume(&channel.sound, channel.volume * gain);
+            }
+        }
+    }
+
+    // Music targets function - The volume each channel is fading to, None for channels that should stop
+    fn music_targets(&self) -> Vec<Option<f32>> {
+        let mut targets = vec![None; self.channels.len()];
+        let duck = if self.ducked { DUCKED_VOLUME } else { 1.0 };
+
+        match self.music_state {
+            MusicState::Menu => targets[MENU_CHANNEL] = Some(duck),
+            // The intense layer plays along from the start (so it stays in time) and fades in with the level
+            MusicState::Gameplay => {
+                targets[GAMEPLAY_CHANNEL] = Some(duck);
+                targets[INTENSE_CHANNEL] = Some(self.intensity * duck);
+            }
+            MusicState::Silent | MusicState::GameOver => {}
+        }
+
+        targets
+    }
+
+    // Play function - Plays an effect at the sfx bus volume, unless too many of it are already playing
+    pub fn play(&mut self, sfx: Sfx) {
+        let volume = self.sfx_gain();
+        if volume <= 0.0 {
+            return;
+        }
+
+        let Some(effect) = self.effects.get_mut(&sfx) else {
+            return;
+        };
+        let (max_instances, length) = sfx.limit();
+        let now = get_time();
+        effect.started.retain(|&started| now - started < length);
+        if effect.started.len() >= max_instances {
+            return;
+        }
+
+        effect.started.push(now);
+        play_sound(
+            &effect.sound,
+            PlaySoundParams {
+                looped: false,
+                volume,
+            },
+        );
+    }
+
+    fn music_gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.music_volume
+        }
+    }
+
+    fn sfx_gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.sfx_volume
+        }
+    }
+}
+
+fn move_towards(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+// Sound Effects Struct - Picks a sound for game events, played through the mixer after each step
+#[derive(Default)]
+pub struct SoundEffects {
+    queued: Vec<Sfx>,
+}
+
+impl SoundEffects {
+    // Play function - Sends the effects queued since the last call to the mixer
+    pub fn play(&mut self, mixer: &mut Mixer) {
+        for sfx in self.queued.drain(..) {
+            mixer.play(sfx);
+        }
+    }
+}
+
+impl EventListener for SoundEffects {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::ShotFired { .. } => self.queued.push(Sfx::Laser),
+            GameEvent::EnemyDestroyed { .. }
+            | GameEvent::PlayerHit { .. }
+            | GameEvent::BossDefeated { .. } => self.queued.push(Sfx::Explosion),
+            GameEvent::PowerUpCollected { .. } | GameEvent::WeaponCollected { .. } => {
+                self.queued.push(Sfx::PowerUp)
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::f32::consts::TAU;
+
+use macroquad::prelude::*;
+
+use crate::events::GameEvent;
+use crate::hitbox::Hitboxes;
+use crate::world::{Shape, ENEMY_BULLET_SPEED, HIT_FLASH_TIME};
+
+// Size of the core and the turrets either side of it
+const CORE_SIZE: f32 = 128.0;
+const TURRET_SIZE: f32 = 64.0;
+// How far the turrets sit from the centre of the core
+const TURRET_OFFSET: f32 = 96.0;
+
+// Hit points of the first boss, later bosses get more
+const CORE_HP: u32 = 40;
+const TURRET_HP: u32 = 15;
+
+// How fast the boss flies in, then sways once it's in place
+const ENTRY_SPEED: f32 = 60.0;
+const SWAY_SPEED: f32 = 50.0;
+// The core sways faster once it has lost its turrets
+const ENRAGED_SWAY_SPEED: f32 = 110.0;
+
+// Seconds between shots for each attack
+const TURRET_FIRE_INTERVAL: f32 = 1.2;
+const CORE_FIRE_INTERVAL: f32 = 2.5;
+const ENRAGED_FIRE_INTERVAL: f32 = 1.6;
+
+// Bolts in the core's ring attack, and the angle between the bolts of its spreads (radians)
+const RING_BOLTS: usize = 12;
+const SPREAD_ANGLE: f32 = 0.25;
+
+// Boss Phase Enum - The script the boss follows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BossPhase {
+    // Flying down into place, can't shoot or be hurt yet
+    Entering,
+    // The turrets fire aimed bolts and shield the core, which fires spreads straight down
+    Turrets,
+    // With the turrets gone the core sways faster, alternating rings of bolts with aimed spreads
+    Enraged,
+}
+
+// Boss Part Struct - The core or one of the turrets, each with its own hit points
+pub struct BossPart {
+    pub shape: Shape,
+    pub hp: u32,
+    // Seconds left of the flash after a hit
+    pub hit_flash: f32,
+    // Where the part sits relative to the core
+    offset: Vec2,
+    fire_timer: f32,
+}
+
+// Boss Struct - A multi-part boss. The core is always the first part, the turrets are removed as
+// they're destroyed and the boss is defeated with the core
+pub struct Boss {
+    pub parts: Vec<BossPart>,
+    pub phase: BossPhase,
+    pub max_hp: u32,
+    // Where the core stops flying in
+    stop_y: f32,
+    // Sideways direction, -1.0 or 1.0
+    direction: f32,
+    // Counts the core's attacks, so the enraged core can alternate between them
+    attacks: u32,
+}
+
+impl Boss {
+    // New function - number is how many bosses have been fought this run, counting this one
+    pub fn new(number: u32, width: f32, height: f32, hitboxes: &Hitboxes) -> Boss {
+        let part = |offset: Vec2, size: f32, hp: u32, hitbox, fire_timer| {
+            let (x, y) = (width / 2.0 + offset.x, -CORE_SIZE + offset.y);
+            BossPart {
+                shape: Shape {
+                    size,
+                    speed: ENTRY_SPEED,
+                    x,
+                    y,
+                    prev_x: x,
+                    prev_y: y,
+                    collided: false,
+                    hitbox,
+                },
+                hp: hp * number,
+                hit_flash: 0.0,
+                offset,
+                fire_timer,
+            }
+        };
+
+        // The turrets fire half an interval apart
+        let parts = vec![
+            part(
+                Vec2::ZERO,
+                CORE_SIZE,
+                CORE_HP,
+                hitboxes.enemy_large.clone(),
+                CORE_FIRE_INTERVAL,
+            ),
+            part(
+                vec2(-TURRET_OFFSET, 16.0),
+                TURRET_SIZE,
+                TURRET_HP,
+                hitboxes.enemy_medium.clone(),
+                TURRET_FIRE_INTERVAL,
+            ),
+            part(
+                vec2(TURRET_OFFSET, 16.0),
+                TURRET_SIZE,
+                TURRET_HP,
+                hitboxes.enemy_medium.clone(),
+                TURRET_FIRE_INTERVAL * 1.5,
+            ),
+        ];
+        let max_hp = parts.iter().map(|part| part.hp).sum();
+
+        Boss {
+            parts,
+            phase: BossPhase::Entering,
+            max_hp,
+            stop_y: height * 0.2,
+            direction: 1.0,
+            attacks: 0,
+        }
+    }
+
+    // Hp function - Hit points left across every part
+    pub fn hp(&self) -> u32 {
+        self.parts.iter().map(|part| part.hp).sum()
+    }
+
+    pub fn defeated(&self) -> bool {
+        self.parts[0].hp == 0
+    }
+
+    pub fn store_position(&mut self) {
+        for part in &mut self.parts {
+            part.shape.store_position();
+        }
+    }
+
+    // Update function - Moves the boss and runs its attacks. Target is the player, None while they're
+    // respawning. Returns the position and velocity of every bolt fired
+    pub fn update(
+        &mut self,
+        delta_time: f32,
+        target: Option<Vec2>,
+        width: f32,
+    ) -> Vec<(Vec2, Vec2)> {
+        let core = vec2(self.parts[0].shape.x, self.parts[0].shape.y);
+
+        // Move the core, the other parts follow it
+        let mut position = core;
+        match self.phase {
+            BossPhase::Entering => {
+                position.y += ENTRY_SPEED * delta_time;
+                if position.y >= self.stop_y {
+                    position.y = self.stop_y;
+                    self.phase = BossPhase::Turrets;
+                }
+            }
+            BossPhase::Turrets | BossPhase::Enraged => {
+                let speed = if self.phase == BossPhase::Enraged {
+                    ENRAGED_SWAY_SPEED
+                } else {
+                    SWAY_SPEED
+                };
+                position.x += self.direction * speed * delta_time;
+
+                // Turn around before the turrets leave the play area
+                let margin = TURRET_OFFSET + TURRET_SIZE / 2.0;
+                if position.x < margin || position.x > width - margin {
+                    self.direction = if position.x < margin { 1.0 } else { -1.0 };
+                    position.x = position.x.clamp(margin, width - margin);
+                }
+            }
+        }
+        for part in &mut self.parts {
+            part.shape.x = position.x + part.offset.x;
+            part.shape.y = position.y + part.offset.y;
+            part.hit_flash = (part.hit_flash - delta_time).max(0.0);
+        }
+
+        let mut shots = vec![];
+        let Some(target) = target else {
+            return shots;
+        };
+        if self.phase == BossPhase::Entering {
+            return shots;
+        }
+
+        let phase = self.phase;
+        for (index, part) in self.parts.iter_mut().enumerate() {
+            part.fire_timer -= delta_time;
+            if part.fire_timer > 0.0 {
+                continue;
+            }
+
+            let position = vec2(part.shape.x, part.shape.y + part.shape.size / 2.0);
+            let aim = (target - position)
+                .try_normalize()
+                .unwrap_or(vec2(0.0, 1.0));
+            if index > 0 {
+                // Turret, one aimed bolt
+                part.fire_timer += TURRET_FIRE_INTERVAL;
+                shots.push((position, aim * ENEMY_BULLET_SPEED));
+            } else if phase == BossPhase::Turrets {
+                part.fire_timer += CORE_FIRE_INTERVAL;
+                shots.extend(spread(position, vec2(0.0, 1.0), 3));
+            } else {
+                part.fire_timer += ENRAGED_FIRE_INTERVAL;
+                self.attacks += 1;
+                if self.attacks % 2 == 1 {
+                    // Ring, turned a little each time so there's always a new gap to find
+                    let turn = self.attacks as f32 * 0.13;
+                    for bolt in 0..RING_BOLTS {
+                        let angle = turn + bolt as f32 / RING_BOLTS as f32 * TAU;
+                        shots.push((position, Vec2::from_angle(angle) * ENEMY_BULLET_SPEED));
+                    }
+                } else {
+                    shots.extend(spread(position, aim, 5));
+                }
+            }
+        }
+
+        shots
+    }
+
+    // Shielded function - The core is shielded until its turrets are gone, and nothing can be hurt while
+    // the boss is flying in
+    pub fn shielded(&self, index: usize) -> bool {
+        self.phase == BossPhase::Entering || (index == 0 && self.parts.len() > 1)
+    }
+
+    // Hit function - Takes damage off a part hit by a bullet, unless it's shielded
+    pub fn hit(&mut self, index: usize, damage: u32, events: &mut Vec<GameEvent>) {
+        if self.shielded(index) {
+            return;
+        }
+
+        let part = &mut self.parts[index];
+        if part.hp == 0 {
+            return;
+        }
+        part.hp = part.hp.saturating_sub(damage);
+        let pos = vec2(part.shape.x, part.shape.y);
+        if part.hp > 0 {
+            part.hit_flash = HIT_FLASH_TIME;
+            events.push(GameEvent::EnemyHit { pos });
+        } else if index == 0 {
+            events.push(GameEvent::BossDefeated { pos });
+        } else {
+            events.push(GameEvent::EnemyDestroyed {
+                size: part.shape.size,
+                pos,
+            });
+        }
+    }
+
+    // Remove destroyed function - Drops destroyed turrets, the core enrages once both are gone
+    pub fn remove_destroyed(&mut self) {
+        let mut index = 0;
+        self.parts.retain(|part| {
+            index += 1;
+            index == 1 || part.hp > 0
+        });
+        if self.parts.len() == 1 && self.phase == BossPhase::Turrets {
+            self.phase = BossPhase::Enraged;
+        }
+    }
+}
+
+// Spread function - count bolts fanned out around a direction, SPREAD_ANGLE apart
+fn spread(position: Vec2, direction: Vec2, count: usize) -> Vec<(Vec2, Vec2)> {
+    let first = -(count as f32 - 1.0) / 2.0 * SPREAD_ANGLE;
+    (0..count)
+        .map(|bolt| {
+            let angle = first + bolt as f32 * SPREAD_ANGLE;
+            (
+                position,
+                Vec2::from_angle(angle).rotate(direction) * ENEMY_BULLET_SPEED,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+
+use crate::powerup::PowerUpKind;
+use crate::weapon::WeaponKind;
+
+// Game Event Enum - Things that happen in the world, emitted by World::step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    // One per shot, bullets is how many were fired with it
+    ShotFired {
+        pos: Vec2,
+        bullets: u32,
+    },
+    EnemyHit {
+        pos: Vec2,
+    },
+    // A bullet hit part of a boss that's shielded and did nothing
+    ShotDeflected {
+        pos: Vec2,
+    },
+    EnemyDestroyed {
+        size: f32,
+        pos: Vec2,
+    },
+    PlayerHit {
+        pos: Vec2,
+    },
+    PlayerRespawned {
+        pos: Vec2,
+    },
+    ExtraLife {
+        lives: u32,
+    },
+    LevelUp {
+        level: u32,
+    },
+    BossIncoming,
+    BossDefeated {
+        pos: Vec2,
+    },
+    PowerUpCollected {
+        kind: PowerUpKind,
+        pos: Vec2,
+    },
+    WeaponCollected {
+        kind: WeaponKind,
+        level: u32,
+        pos: Vec2,
+    },
+}
+
+// Event Listener Trait - A system that reacts to game events (audio, particles, HUD, stats etc.)
+pub trait EventListener {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+// Dispatch function - Sends every event, in order, to every listener
+pub fn dispatch(events: &[GameEvent], listeners: &mut [&mut dyn EventListener]) {
+    for event in events {
+        for listener in listeners.iter_mut() {
+            listener.on_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Stats;
+
+    // Recorder Struct - Keeps every event it's sent
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<GameEvent>,
+    }
+
+    impl EventListener for Recorder {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.events.push(*event);
+        }
+    }
+
+    #[test]
+    fn every_listener_gets_every_event_in_order() {
+        let events = [
+            GameEvent::ShotFired {
+                pos: Vec2::ZERO,
+                bullets: 3,
+            },
+            GameEvent::EnemyHit { pos: Vec2::ZERO },
+            GameEvent::EnemyDestroyed {
+                size: 32.0,
+                pos: Vec2::ZERO,
+            },
+            GameEvent::PlayerHit { pos: Vec2::ZERO },
+            GameEvent::LevelUp { level: 2 },
+        ];
+        let mut first = Recorder::default();
+        let mut second = Recorder::default();
+        let mut stats = Stats::new();
+        dispatch(&events, &mut [&mut first, &mut stats, &mut second]);
+
+        assert_eq!(first.events, events);
+        assert_eq!(second.events, events);
+        assert_eq!(stats.shots_fired, 3);
+        assert_eq!(stats.shots_hit, 2);
+        assert_eq!(stats.accuracy(), 66);
+        assert_eq!(stats.enemies_destroyed, 1);
+        assert_eq!(stats.hits_taken, 1);
+        assert_eq!(stats.level_reached, 2);
+    }
+}
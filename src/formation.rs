@@ -0,0 +1,221 @@
+use macroquad::prelude::*;
+
+// Size of the formation grid, and the distance between slots
+pub const COLUMNS: u32 = 8;
+pub const ROWS: u32 = 5;
+const SLOT_SPACING: f32 = 56.0;
+// Where the top row of the formation sits
+const FORMATION_TOP: f32 = 80.0;
+// How far the formation sways either side of the centre, and how fast (radians per second)
+const SWAY_AMPLITUDE: f32 = 40.0;
+const SWAY_FREQUENCY: f32 = 0.6;
+
+// Spline Struct - A Catmull-Rom spline through every one of its points, used for flight paths
+#[derive(Clone, Debug)]
+pub struct Spline {
+    points: Vec<Vec2>,
+}
+
+impl Spline {
+    // New function - Needs at least two points
+    pub fn new(points: Vec<Vec2>) -> Spline {
+        assert!(points.len() >= 2, "a spline needs at least two points");
+        Spline { points }
+    }
+
+    // Scaled function - Level files give points across the play area from 0.0 to 1.0, this turns them
+    // into pixels
+    pub fn scaled(&self, size: Vec2) -> Spline {
+        Spline::new(self.points.iter().map(|&point| point * size).collect())
+    }
+
+    pub fn start(&self) -> Vec2 {
+        self.points[0]
+    }
+
+    fn segments(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    // Point function - t goes from 0.0 at the first point to 1.0 at the second and so on up to the last
+    pub fn point(&self, t: f32) -> Vec2 {
+        let t = t.clamp(0.0, self.segments() as f32);
+        let segment = (t as usize).min(self.segments() - 1);
+        // The first and last points are repeated so the spline runs all the way to them
+        let point = |index: isize| self.points[index.clamp(0, self.segments() as isize) as usize];
+        let index = segment as isize;
+        catmull_rom(
+            point(index - 1),
+            point(index),
+            point(index + 1),
+            point(index + 2),
+            t - segment as f32,
+        )
+    }
+
+    // Advance function - Moves t along by about distance pixels, estimating each segment's length as the
+    // straight line between its points
+    pub fn advance(&self, t: f32, distance: f32) -> f32 {
+        let segment = (t.max(0.0) as usize).min(self.segments() - 1);
+        let length = self.points[segment].distance(self.points[segment + 1]);
+        t + distance / length.max(1.0)
+    }
+
+    pub fn finished(&self, t: f32) -> bool {
+        t >= self.segments() as f32
+    }
+
+    // Dive function - A dive from a slot in the formation: a loop out to the side, down through where the
+    // player is and off the bottom of the play area
+    pub fn dive(from: Vec2, target: Vec2, width: f32, height: f32) -> Spline {
+        let side = if from.x < width / 2.0 { -1.0 } else { 1.0 };
+        Spline::new(vec![
+            from,
+            from + vec2(side * 40.0, -40.0),
+            from + vec2(side * 80.0, 20.0),
+            target,
+            vec2(target.x, height + SLOT_SPACING * 2.0),
+        ])
+    }
+}
+
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+// Slot Struct - A place in the formation grid, column 0 is on the left and row 0 at the top
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Slot {
+    pub column: u32,
+    pub row: u32,
+}
+
+// Flight Enum - What an enemy flying a path or in the formation is doing
+#[derive(Clone, Debug)]
+pub enum Flight {
+    // Following a spline, t along it
+    Path { spline: Spline, t: f32 },
+    // Flying to its slot after a path
+    Joining,
+    // In its slot, swaying with the formation
+    Holding,
+}
+
+// Formation Struct - The grid enemies settle into, swaying from side to side together
+pub struct Formation {
+    width: f32,
+    time: f32,
+}
+
+impl Formation {
+    pub fn new(width: f32) -> Formation {
+        Formation { width, time: 0.0 }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.time += delta_time;
+    }
+
+    // Slot position function - Where a slot is right now, the grid is centred in the play area
+    pub fn slot_position(&self, slot: Slot) -> Vec2 {
+        let sway = (self.time * SWAY_FREQUENCY).sin() * SWAY_AMPLITUDE;
+        let column = slot.column as f32 - (COLUMNS - 1) as f32 / 2.0;
+        vec2(
+            self.width / 2.0 + column * SLOT_SPACING + sway,
+            FORMATION_TOP + slot.row as f32 * SLOT_SPACING,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splines_pass_through_every_point() {
+        let points = vec![
+            vec2(0.0, 0.0),
+            vec2(100.0, 50.0),
+            vec2(200.0, 0.0),
+            vec2(300.0, 100.0),
+        ];
+        let spline = Spline::new(points.clone());
+        for (index, &point) in points.iter().enumerate() {
+            assert!(spline.point(index as f32).distance(point) < 0.001);
+        }
+        assert_eq!(spline.start(), points[0]);
+        assert!(spline.point(-1.0).distance(points[0]) < 0.001);
+        assert!(spline.point(10.0).distance(points[3]) < 0.001);
+
+        let scaled = Spline::new(vec![vec2(0.5, 0.0), vec2(0.5, 1.0)]).scaled(vec2(800.0, 600.0));
+        assert_eq!(scaled.start(), vec2(400.0, 0.0));
+        assert!(scaled.point(1.0).distance(vec2(400.0, 600.0)) < 0.001);
+    }
+
+    #[test]
+    fn advancing_covers_about_the_distance_asked_for() {
+        let spline = Spline::new(vec![vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(100.0, 300.0)]);
+        let t = spline.advance(0.0, 50.0);
+        assert_eq!(t, 0.5);
+        assert!(!spline.finished(t));
+
+        // Every segment is covered at the same speed, whatever its length
+        let mut t = 0.0;
+        let mut steps = 0;
+        while !spline.finished(t) {
+            t = spline.advance(t, 1.0);
+            steps += 1;
+        }
+        assert!((399..=401).contains(&steps));
+    }
+
+    #[test]
+    fn dives_loop_out_through_the_target_and_off_the_bottom() {
+        let from = vec2(200.0, 100.0);
+        let target = vec2(500.0, 500.0);
+        let dive = Spline::dive(from, target, 800.0, 600.0);
+        assert_eq!(dive.start(), from);
+        // Away from the nearest side first
+        assert!(dive.point(1.0).x < from.x);
+        assert!(dive.point(3.0).distance(target) < 0.001);
+        assert!(dive.point(4.0).y > 600.0);
+
+        let from = vec2(600.0, 100.0);
+        let dive = Spline::dive(from, target, 800.0, 600.0);
+        assert!(dive.point(1.0).x > from.x);
+    }
+
+    #[test]
+    fn formation_sways_together() {
+        let mut formation = Formation::new(800.0);
+        let left = Slot { column: 0, row: 0 };
+        let right = Slot {
+            column: COLUMNS - 1,
+            row: ROWS - 1,
+        };
+        let centre = (formation.slot_position(left).x + formation.slot_position(right).x) / 2.0;
+        assert_eq!(centre, 400.0);
+
+        // Both slots move the same way, keeping their spacing
+        let (before_left, before_right) = (
+            formation.slot_position(left),
+            formation.slot_position(right),
+        );
+        formation.update(1.0);
+        let sway = formation.slot_position(left) - before_left;
+        assert!(sway.x != 0.0 && sway.y == 0.0);
+        assert!((formation.slot_position(right) - before_right).distance(sway) < 0.001);
+        assert_eq!(
+            before_right - before_left,
+            vec2(
+                (COLUMNS - 1) as f32 * SLOT_SPACING,
+                (ROWS - 1) as f32 * SLOT_SPACING
+            )
+        );
+    }
+}
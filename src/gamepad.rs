@@ -0,0 +1,106 @@
+use gilrs::{Axis, Button, EventType, Gilrs};
+use macroquad::prelude::*;
+
+use crate::input::Action;
+
+// How far the stick has to move before it counts, so worn sticks don't drift the player
+const STICK_DEADZONE: f32 = 0.2;
+
+// Gamepads Struct - Every connected gamepad, read through the same actions as the keyboard
+pub struct Gamepads {
+    // None when the platform has no gamepad support, the game then stays keyboard-only
+    gilrs: Option<Gilrs>,
+    // Buttons pressed since the last update, on any gamepad
+    pressed: Vec<Button>,
+}
+
+impl Gamepads {
+    pub fn new() -> Gamepads {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                eprintln!("Gamepads unavailable: {}", error);
+                None
+            }
+        };
+
+        Gamepads {
+            gilrs,
+            pressed: vec![],
+        }
+    }
+
+    // Update function - Call once per frame, before anything reads the gamepads
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        if let Some(gilrs) = self.gilrs.as_mut() {
+            while let Some(event) = gilrs.next_event() {
+                if let EventType::ButtonPressed(button, _) = event.event {
+                    self.pressed.push(button);
+                }
+            }
+        }
+    }
+
+    // Is down function - Whether any button mapped to the action is held on any gamepad
+    pub fn is_down(&self, action: Action) -> bool {
+        let Some(gilrs) = self.gilrs.as_ref() else {
+            return false;
+        };
+        gilrs.gamepads().any(|(_, gamepad)| {
+            buttons(action)
+                .iter()
+                .any(|&button| gamepad.is_pressed(button))
+        })
+    }
+
+    // Is pressed function - Whether a button mapped to the action was pressed this frame
+    pub fn is_pressed(&self, action: Action) -> bool {
+        buttons(action)
+            .iter()
+            .any(|button| self.pressed.contains(button))
+    }
+
+    // Stick function - Left stick of the first gamepad that is being moved, with the deadzone taken out.
+    // Each axis is -1.0 to 1.0, with y pointing down like the screen
+    pub fn stick(&self) -> Vec2 {
+        let Some(gilrs) = self.gilrs.as_ref() else {
+            return Vec2::ZERO;
+        };
+        gilrs
+            .gamepads()
+            .map(|(_, gamepad)| {
+                apply_deadzone(vec2(
+                    gamepad.value(Axis::LeftStickX),
+                    -gamepad.value(Axis::LeftStickY),
+                ))
+            })
+            .find(|stick| *stick != Vec2::ZERO)
+            .unwrap_or(Vec2::ZERO)
+    }
+}
+
+// Buttons function - The gamepad buttons for each action (A/B/Start/Back on an Xbox layout)
+fn buttons(action: Action) -> &'static [Button] {
+    match action {
+        Action::MoveUp => &[Button::DPadUp],
+        Action::MoveDown => &[Button::DPadDown],
+        Action::MoveLeft => &[Button::DPadLeft],
+        Action::MoveRight => &[Button::DPadRight],
+        Action::Fire => &[Button::South, Button::West],
+        Action::Pause => &[Button::Start],
+        Action::Confirm => &[Button::South],
+        Action::Back => &[Button::East],
+        Action::Mute => &[Button::Select],
+    }
+}
+
+// Apply deadzone function - Zero inside the deadzone, then scaled so movement starts from 0 at its edge
+fn apply_deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0);
+    stick * (scaled / length)
+}
@@ -0,0 +1,378 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+// Hitbox Enum - The part of an entity that can be hit. All sizes and points are in units of the
+// entity's size and centred on its position, so one hitbox fits every size of enemy
+#[derive(Clone)]
+pub enum Hitbox {
+    // Axis aligned box
+    Aabb {
+        w: f32,
+        h: f32,
+    },
+    Circle {
+        radius: f32,
+    },
+    // Convex polygon, points in order around the centre
+    Polygon(Vec<Vec2>),
+    // Opaque pixels of a sprite frame, stretched over a w by h box
+    Mask {
+        w: f32,
+        h: f32,
+        mask: Arc<PixelMask>,
+    },
+}
+
+// Hitboxes Struct - Which hitbox each type of entity uses
+#[derive(Clone)]
+pub struct Hitboxes {
+    pub player: Hitbox,
+    pub bullet: Hitbox,
+    pub bolt: Hitbox,
+    pub enemy_small: Hitbox,
+    pub enemy_medium: Hitbox,
+    pub enemy_large: Hitbox,
+}
+
+impl Default for Hitboxes {
+    // Default function - Rough shapes for when the sprites aren't loaded (e.g. running headless)
+    fn default() -> Hitboxes {
+        Hitboxes {
+            player: Hitbox::Polygon(vec![
+                vec2(0.0, -0.7),
+                vec2(0.45, 0.3),
+                vec2(0.3, 0.7),
+                vec2(-0.3, 0.7),
+                vec2(-0.45, 0.3),
+            ]),
+            bullet: Hitbox::Aabb { w: 0.3, h: 0.8 },
+            bolt: Hitbox::Circle { radius: 0.25 },
+            enemy_small: Hitbox::Circle { radius: 0.45 },
+            enemy_medium: Hitbox::Aabb { w: 1.0, h: 0.6 },
+            enemy_large: Hitbox::Circle { radius: 0.45 },
+        }
+    }
+}
+
+// Pixel Mask Struct - Which pixels of a sprite frame are opaque
+pub struct PixelMask {
+    width: usize,
+    height: usize,
+    opaque: Vec<bool>,
+}
+
+impl PixelMask {
+    // From image function - source is the frame within the sprite sheet
+    pub fn from_image(image: &Image, source: Rect) -> PixelMask {
+        let width = source.w as usize;
+        let height = source.h as usize;
+        let mut opaque = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = image.get_pixel(source.x as u32 + x as u32, source.y as u32 + y as u32);
+                opaque.push(pixel.a > 0.0);
+            }
+        }
+
+        PixelMask {
+            width,
+            height,
+            opaque,
+        }
+    }
+
+    // Opaque at function - u and v go from 0.0 to 1.0 across the mask
+    fn opaque_at(&self, u: f32, v: f32) -> bool {
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return false;
+        }
+        let x = (u * self.width as f32) as usize;
+        let y = (v * self.height as f32) as usize;
+        self.opaque[y * self.width + x]
+    }
+}
+
+impl Hitbox {
+    // Overlaps function - Whether this hitbox at (position, size) touches other at (other_position, other_size)
+    pub fn overlaps(
+        &self,
+        position: Vec2,
+        size: f32,
+        other: &Hitbox,
+        other_position: Vec2,
+        other_size: f32,
+    ) -> bool {
+        let a = self.place(position, size);
+        let b = other.place(other_position, other_size);
+        a.bounds().overlaps(&b.bounds()) && placed_overlap(&a, &b)
+    }
+
+    // Bounds function - Smallest rect around the hitbox, used by the broadphase
+    pub fn bounds(&self, position: Vec2, size: f32) -> Rect {
+        match self {
+            Hitbox::Aabb { w, h } | Hitbox::Mask { w, h, .. } => Rect::new(
+                position.x - w * size / 2.0,
+                position.y - h * size / 2.0,
+                w * size,
+                h * size,
+            ),
+            Hitbox::Circle { radius } => Rect::new(
+                position.x - radius * size,
+                position.y - radius * size,
+                radius * size * 2.0,
+                radius * size * 2.0,
+            ),
+            Hitbox::Polygon(points) => {
+                let min = points.iter().fold(Vec2::MAX, |min, point| min.min(*point));
+                let max = points.iter().fold(Vec2::MIN, |max, point| max.max(*point));
+                Rect::new(
+                    position.x + min.x * size,
+                    position.y + min.y * size,
+                    (max.x - min.x) * size,
+                    (max.y - min.y) * size,
+                )
+            }
+        }
+    }
+
+    // Place function - The hitbox in world coordinates
+    fn place(&self, position: Vec2, size: f32) -> Placed<'_> {
+        match self {
+            Hitbox::Aabb { .. } => Placed::Rect(self.bounds(position, size)),
+            Hitbox::Circle { radius } => Placed::Circle(position, radius * size),
+            Hitbox::Polygon(points) => Placed::Polygon(
+                points
+                    .iter()
+                    .map(|point| position + *point * size)
+                    .collect(),
+            ),
+            Hitbox::Mask { mask, .. } => Placed::Mask(self.bounds(position, size), mask),
+        }
+    }
+}
+
+// Placed Enum - A hitbox in world coordinates
+enum Placed<'a> {
+    Rect(Rect),
+    Circle(Vec2, f32),
+    Polygon(Vec<Vec2>),
+    Mask(Rect, &'a PixelMask),
+}
+
+impl Placed<'_> {
+    fn bounds(&self) -> Rect {
+        match self {
+            Placed::Rect(rect) | Placed::Mask(rect, _) => *rect,
+            Placed::Circle(centre, radius) => Rect::new(
+                centre.x - radius,
+                centre.y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            ),
+            Placed::Polygon(points) => {
+                let min = points.iter().fold(Vec2::MAX, |min, point| min.min(*point));
+                let max = points.iter().fold(Vec2::MIN, |max, point| max.max(*point));
+                Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+            }
+        }
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Placed::Rect(rect) => rect.contains(point),
+            Placed::Circle(centre, radius) => centre.distance_squared(point) <= radius * radius,
+            Placed::Polygon(points) => polygon_contains(points, point),
+            Placed::Mask(rect, mask) => {
+                mask.opaque_at((point.x - rect.x) / rect.w, (point.y - rect.y) / rect.h)
+            }
+        }
+    }
+}
+
+// Placed overlap function - Picks the right test for each pair of hitbox types
+fn placed_overlap(a: &Placed, b: &Placed) -> bool {
+    match (a, b) {
+        (Placed::Mask(rect, mask), other) | (other, Placed::Mask(rect, mask)) => {
+            mask_overlaps(*rect, mask, other)
+        }
+        (Placed::Rect(a), Placed::Rect(b)) => a.overlaps(b),
+        (Placed::Circle(a, a_radius), Placed::Circle(b, b_radius)) => {
+            a.distance_squared(*b) <= (a_radius + b_radius) * (a_radius + b_radius)
+        }
+        (Placed::Circle(centre, radius), Placed::Rect(rect))
+        | (Placed::Rect(rect), Placed::Circle(centre, radius)) => {
+            let closest = centre.clamp(rect.point(), rect.point() + rect.size());
+            centre.distance_squared(closest) <= radius * radius
+        }
+        (Placed::Circle(centre, radius), Placed::Polygon(points))
+        | (Placed::Polygon(points), Placed::Circle(centre, radius)) => {
+            circle_polygon_overlap(*centre, *radius, points)
+        }
+        (Placed::Rect(rect), Placed::Polygon(points))
+        | (Placed::Polygon(points), Placed::Rect(rect)) => {
+            polygons_overlap(&rect_points(*rect), points)
+        }
+        (Placed::Polygon(a), Placed::Polygon(b)) => polygons_overlap(a, b),
+    }
+}
+
+// Mask overlaps function - Checks the centre of every opaque pixel that could touch other
+fn mask_overlaps(rect: Rect, mask: &PixelMask, other: &Placed) -> bool {
+    let Some(area) = rect.intersect(other.bounds()) else {
+        return false;
+    };
+    let pixel_w = rect.w / mask.width as f32;
+    let pixel_h = rect.h / mask.height as f32;
+    let first_x = ((area.x - rect.x) / pixel_w).floor() as usize;
+    let first_y = ((area.y - rect.y) / pixel_h).floor() as usize;
+    let last_x = (((area.x + area.w - rect.x) / pixel_w).ceil() as usize).min(mask.width);
+    let last_y = (((area.y + area.h - rect.y) / pixel_h).ceil() as usize).min(mask.height);
+
+    for y in first_y..last_y {
+        for x in first_x..last_x {
+            if mask.opaque[y * mask.width + x] {
+                let centre = vec2(
+                    rect.x + (x as f32 + 0.5) * pixel_w,
+                    rect.y + (y as f32 + 0.5) * pixel_h,
+                );
+                if other.contains(centre) {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn rect_points(rect: Rect) -> Vec<Vec2> {
+    vec![
+        rect.point(),
+        vec2(rect.x + rect.w, rect.y),
+        rect.point() + rect.size(),
+        vec2(rect.x, rect.y + rect.h),
+    ]
+}
+
+// Separating axis test - Convex polygons overlap unless one of their edge normals separates them
+fn polygons_overlap(a: &[Vec2], b: &[Vec2]) -> bool {
+    edge_normals(a)
+        .chain(edge_normals(b))
+        .all(|axis| ranges_overlap(project(a, axis), project(b, axis)))
+}
+
+fn circle_polygon_overlap(centre: Vec2, radius: f32, points: &[Vec2]) -> bool {
+    // Besides the edge normals, the axis towards the nearest corner can separate a circle from a polygon
+    let nearest = points
+        .iter()
+        .min_by(|a, b| {
+            a.distance_squared(centre)
+                .total_cmp(&b.distance_squared(centre))
+        })
+        .copied()
+        .unwrap_or(centre);
+    let corner_axis = (nearest - centre).normalize_or_zero();
+
+    edge_normals(points)
+        .map(|axis| axis.normalize_or_zero())
+        .chain(std::iter::once(corner_axis))
+        .filter(|axis| *axis != Vec2::ZERO)
+        .all(|axis| {
+            let projected = centre.dot(axis);
+            ranges_overlap(
+                project(points, axis),
+                (projected - radius, projected + radius),
+            )
+        })
+}
+
+fn polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+    // Inside a convex polygon the point is on the same side of every edge
+    let mut sign = 0.0;
+    for (index, a) in points.iter().enumerate() {
+        let b = points[(index + 1) % points.len()];
+        let cross = (b - *a).perp_dot(point - *a);
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    true
+}
+
+fn edge_normals(points: &[Vec2]) -> impl Iterator<Item = Vec2> + '_ {
+    (0..points.len()).map(move |index| (points[(index + 1) % points.len()] - points[index]).perp())
+}
+
+fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+    points
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), point| {
+            let projected = point.dot(axis);
+            (min.min(projected), max.max(projected))
+        })
+}
+
+fn ranges_overlap(a: (f32, f32), b: (f32, f32)) -> bool {
+    a.0 <= b.1 && b.0 <= a.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hitboxes function - One of each type, all about a unit across
+    fn hitboxes() -> [Hitbox; 4] {
+        let mut image = Image::gen_image_color(4, 4, Color::new(0.0, 0.0, 0.0, 0.0));
+        for x in 0..4 {
+            for y in 0..4 {
+                if (1..3).contains(&x) && (1..3).contains(&y) {
+                    image.set_pixel(x, y, WHITE);
+                }
+            }
+        }
+        [
+            Hitbox::Aabb { w: 1.0, h: 1.0 },
+            Hitbox::Circle { radius: 0.5 },
+            Hitbox::Polygon(vec![
+                vec2(0.0, -0.5),
+                vec2(0.5, 0.0),
+                vec2(0.0, 0.5),
+                vec2(-0.5, 0.0),
+            ]),
+            // Only the middle half is opaque
+            Hitbox::Mask {
+                w: 2.0,
+                h: 2.0,
+                mask: Arc::new(PixelMask::from_image(&image, Rect::new(0.0, 0.0, 4.0, 4.0))),
+            },
+        ]
+    }
+
+    #[test]
+    fn every_pair_overlaps_both_ways() {
+        let hitboxes = hitboxes();
+        for a in &hitboxes {
+            for b in &hitboxes {
+                // Touching in the middle, far apart, and corner to corner where only two boxes touch
+                let boxes = matches!((a, b), (Hitbox::Aabb { .. }, Hitbox::Aabb { .. }));
+                for (offset, expected) in [
+                    (vec2(0.3, 0.2), true),
+                    (vec2(5.0, 0.0), false),
+                    (vec2(0.95, 0.95), boxes),
+                ] {
+                    let position = vec2(10.0, 10.0);
+                    assert_eq!(
+                        a.overlaps(position, 10.0, b, position + offset * 10.0, 10.0),
+                        expected
+                    );
+                    assert_eq!(
+                        b.overlaps(position + offset * 10.0, 10.0, a, position, 10.0),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use macroquad::prelude::*;
+
+use crate::events::{EventListener, GameEvent};
+use crate::powerup::{Pickup, PowerUpKind};
+use crate::weapon::WeaponKind;
+use crate::world::World;
+
+// How long banners ("Level N", "Extra Life!") stay on screen, in seconds
+const BANNER_TIME: f32 = 2.0;
+
+// Size of the boss health bar
+const BOSS_BAR_WIDTH: f32 = 300.0;
+const BOSS_BAR_HEIGHT: f32 = 12.0;
+
+// Width of the bar under each active power-up, full when it has just been picked up
+const POWER_UP_BAR_WIDTH: f32 = 120.0;
+
+// Hud Struct - Score, high score, lives and banners drawn over the game
+#[derive(Default)]
+pub struct Hud {
+    banner: Option<(String, f32)>,
+}
+
+impl Hud {
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some((_, time_left)) = &mut self.banner {
+            *time_left -= delta_time;
+            if *time_left <= 0.0 {
+                self.banner = None;
+            }
+        }
+    }
+
+    pub fn draw(&self, world: &World, high_score: u32, replay: bool) {
+        // Draw scores
+        draw_text(
+            format!("Score: {}", world.score).as_str(),
+            10.0,
+            35.0,
+            25.0,
+            WHITE,
+        );
+
+        draw_text(
+            format!("Lives: {}", world.lives).as_str(),
+            10.0,
+            65.0,
+            25.0,
+            WHITE,
+        );
+
+        // Active power-ups under the lives, each with the seconds it has left
+        let mut y = 95.0;
+        for kind in PowerUpKind::ALL {
+            let time_left = world.active_power_ups.time_left(kind);
+            if time_left <= 0.0 {
+                continue;
+            }
+            let (color, _) = power_up_look(kind);
+            draw_text(
+                format!("{} {:.0}", kind.name(), time_left.ceil()).as_str(),
+                10.0,
+                y,
+                20.0,
+                color,
+            );
+            let fraction = time_left / kind.duration();
+            draw_rectangle(10.0, y + 5.0, POWER_UP_BAR_WIDTH * fraction, 3.0, color);
+            y += 30.0;
+        }
+
+        let highscore_text = format!("High Score: {}", high_score);
+        let text_dimensions = measure_text(highscore_text.as_str(), None, 25, 1.0);
+        draw_text(
+            highscore_text.as_str(),
+            screen_width() - text_dimensions.width - 10.0,
+            35.0,
+            25.0,
+            WHITE,
+        );
+
+        // Weapon and its level under the high score
+        let weapon_text = format!("{} Lv {}", world.weapon.kind.name(), world.weapon.level);
+        let text_dimensions = measure_text(weapon_text.as_str(), None, 25, 1.0);
+        draw_text(
+            weapon_text.as_str(),
+            screen_width() - text_dimensions.width - 10.0,
+            65.0,
+            25.0,
+            world.weapon.kind.sprite().color,
+        );
+
+        // Let the viewer know this is a replay
+        if replay {
+            let text = "REPLAY";
+            let text_dimensions = measure_text(text, None, 25, 1.0);
+            draw_text(
+                text,
+                screen_width() / 2.0 - text_dimensions.width / 2.0,
+                35.0,
+                25.0,
+                RED,
+            );
+        }
+
+        // Boss health, across all of its parts
+        if let Some(boss) = &world.boss {
+            let x = screen_width() / 2.0 - BOSS_BAR_WIDTH / 2.0;
+            let y = 60.0;
+            let fraction = boss.hp() as f32 / boss.max_hp as f32;
+            draw_text("BOSS", x - 60.0, y + BOSS_BAR_HEIGHT, 25.0, WHITE);
+            draw_rectangle(x, y, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT, DARKGRAY);
+            draw_rectangle(x, y, BOSS_BAR_WIDTH * fraction, BOSS_BAR_HEIGHT, RED);
+            draw_rectangle_lines(x, y, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT, 2.0, WHITE);
+        }
+
+        // Announce a new level, extra life or boss
+        if let Some((text, _)) = &self.banner {
+            let text_dimensions = measure_text(text.as_str(), None, 50, 1.0);
+            draw_text(
+                text.as_str(),
+                screen_width() / 2.0 - text_dimensions.width / 2.0,
+                screen_height() / 3.0,
+                50.0,
+                YELLOW,
+            );
+        }
+    }
+}
+
+// Power up look function - The colour and letter a power-up is shown with, in the HUD and on its pickup
+pub fn power_up_look(kind: PowerUpKind) -> (Color, &'static str) {
+    match kind {
+        PowerUpKind::SpreadShot => (ORANGE, "S"),
+        PowerUpKind::RapidFire => (YELLOW, "R"),
+        PowerUpKind::Shield => (SKYBLUE, "O"),
+        PowerUpKind::ScoreMultiplier => (GREEN, "x2"),
+    }
+}
+
+// Pickup look function - Weapon pickups take the colour of their projectiles
+pub fn pickup_look(pickup: Pickup) -> (Color, &'static str) {
+    match pickup {
+        Pickup::PowerUp(kind) => power_up_look(kind),
+        Pickup::Weapon(kind) => {
+            let letter = match kind {
+                WeaponKind::Laser => "L",
+                WeaponKind::Bolt => "B",
+                WeaponKind::WaveBeam => "W",
+                WeaponKind::Homing => "H",
+            };
+            (kind.sprite().color, letter)
+        }
+    }
+}
+
+impl EventListener for Hud {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::LevelUp { level } => {
+                self.banner = Some((format!("Level {}", level), BANNER_TIME))
+            }
+            GameEvent::ExtraLife { .. } => {
+                self.banner = Some(("Extra Life!".to_string(), BANNER_TIME))
+            }
+            GameEvent::BossIncoming => {
+                self.banner = Some(("Warning: Boss!".to_string(), BANNER_TIME))
+            }
+            GameEvent::BossDefeated { .. } => {
+                self.banner = Some(("Boss Defeated!".to_string(), BANNER_TIME))
+            }
+            GameEvent::WeaponCollected { kind, level, .. } => {
+                self.banner = Some((format!("{} Lv {}", kind.name(), level), BANNER_TIME))
+            }
+            _ => {}
+        }
+    }
+}
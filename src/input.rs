@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use macroquad::prelude::*;
+
+// Action Enum - Something the player can do, bound to one or more keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+    Confirm,
+    Back,
+    Mute,
+}
+
+impl Action {
+    // Every action, in the order shown on the controls screen
+    pub const ALL: [Action; 9] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+        Action::Mute,
+    ];
+
+    // Name shown on the controls screen
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Fire => "Fire",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::Back => "Back",
+            Action::Mute => "Mute",
+        }
+    }
+
+    // Name used in the profile
+    pub fn id(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+            Action::Mute => "mute",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.id() == id)
+    }
+}
+
+// Bindings Struct - The keys bound to each action
+#[derive(Clone)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Bindings {
+    // Default function - WASD, Vim style HJKL and the arrow keys all move
+    fn default() -> Bindings {
+        let mut bindings = Bindings {
+            keys: BTreeMap::new(),
+        };
+        for action in Action::ALL {
+            bindings.reset(action);
+        }
+        bindings
+    }
+}
+
+impl Bindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    // Set function - Replaces the action's keys. Returns false and leaves them as they were when that would
+    // leave Confirm or Back without a key of their own, as the menus can't be used without both
+    pub fn set(&mut self, action: Action, keys: Vec<KeyCode>) -> bool {
+        let other = match action {
+            Action::Confirm => Some(Action::Back),
+            Action::Back => Some(Action::Confirm),
+            _ => None,
+        };
+        if let Some(other) = other {
+            let other_keys = self.keys(other);
+            if !keys.iter().any(|key| !other_keys.contains(key))
+                || !other_keys.iter().any(|key| !keys.contains(key))
+            {
+                return false;
+            }
+        }
+
+        self.keys.insert(action, keys);
+        true
+    }
+
+    // Reset function - Puts an action back to its default keys
+    pub fn reset(&mut self, action: Action) {
+        let keys = match action {
+            Action::MoveUp => vec![KeyCode::W, KeyCode::K, KeyCode::Up],
+            Action::MoveDown => vec![KeyCode::S, KeyCode::J, KeyCode::Down],
+            Action::MoveLeft => vec![KeyCode::A, KeyCode::H, KeyCode::Left],
+            Action::MoveRight => vec![KeyCode::D, KeyCode::L, KeyCode::Right],
+            Action::Fire => vec![KeyCode::Space],
+            Action::Pause => vec![KeyCode::Escape, KeyCode::P],
+            // Not Space, it fires and a run often ends with it held down
+            Action::Confirm => vec![KeyCode::Enter],
+            Action::Back => vec![KeyCode::Escape, KeyCode::Backspace],
+            Action::Mute => vec![KeyCode::M],
+        };
+        self.keys.insert(action, keys);
+    }
+
+    // Is down function - Whether any key bound to the action is held
+    pub fn is_down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| is_key_down(key))
+    }
+
+    // Is pressed function - Whether any key bound to the action was pressed this frame
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| is_key_pressed(key))
+    }
+}
+
+// Keys that can be bound, with the names used on screen and in the profile
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Space, "Space"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::LeftShift, "LeftShift"),
+    (KeyCode::RightShift, "RightShift"),
+    (KeyCode::LeftControl, "LeftControl"),
+    (KeyCode::RightControl, "RightControl"),
+    (KeyCode::LeftAlt, "LeftAlt"),
+    (KeyCode::RightAlt, "RightAlt"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Kp0, "Keypad0"),
+    (KeyCode::Kp1, "Keypad1"),
+    (KeyCode::Kp2, "Keypad2"),
+    (KeyCode::Kp3, "Keypad3"),
+    (KeyCode::Kp4, "Keypad4"),
+    (KeyCode::Kp5, "Keypad5"),
+    (KeyCode::Kp6, "Keypad6"),
+    (KeyCode::Kp7, "Keypad7"),
+    (KeyCode::Kp8, "Keypad8"),
+    (KeyCode::Kp9, "Keypad9"),
+    (KeyCode::KpEnter, "KeypadEnter"),
+];
+
+// Key name function - None for keys that can't be bound
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirm_and_back_keep_a_key_of_their_own() {
+        let mut bindings = Bindings::default();
+        assert!(!bindings.keys(Action::Confirm).contains(&KeyCode::Space));
+
+        assert!(!bindings.set(Action::Confirm, vec![]));
+        assert!(!bindings.set(Action::Confirm, vec![KeyCode::Escape]));
+        assert!(!bindings.set(Action::Back, vec![KeyCode::Enter]));
+        assert_eq!(bindings.keys(Action::Confirm), [KeyCode::Enter]);
+
+        // Sharing a key is fine while each still has another
+        assert!(bindings.set(Action::Confirm, vec![KeyCode::Enter, KeyCode::Escape]));
+        assert!(bindings.set(Action::Back, vec![KeyCode::Enter, KeyCode::Backspace]));
+        assert!(!bindings.set(Action::Confirm, vec![KeyCode::Enter]));
+        assert!(bindings.set(Action::Fire, vec![]));
+    }
+}
@@ -0,0 +1,120 @@
+// Number of entries kept in the leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Score Entry Struct - One line of the leaderboard
+#[derive(Clone)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub level: u32,
+    // Seconds since 1970-01-01 (UTC)
+    pub date: u64,
+    pub seed: u64,
+}
+
+// Leaderboard Struct - Top scores, best first
+#[derive(Default)]
+pub struct Leaderboard {
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl Leaderboard {
+    // Qualifies function - Whether a score is good enough to get onto the leaderboard
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < LEADERBOARD_SIZE
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    // Insert function - Adds an entry in score order, returns its position if it made the cut.
+    // Equal scores keep the older entry first
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        let position = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        if position >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        self.entries.insert(position, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(position)
+    }
+}
+
+// Format date function - Turns seconds since 1970 into "YYYY-MM-DD"
+pub fn format_date(date: u64) -> String {
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let days = (date / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> ScoreEntry {
+        ScoreEntry {
+            name: name.to_string(),
+            score,
+            level: 1,
+            date: 0,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn insert_keeps_the_best_scores_in_order() {
+        let mut leaderboard = Leaderboard::default();
+        assert!(!leaderboard.qualifies(0));
+        assert_eq!(leaderboard.insert(entry("AAA", 100)), Some(0));
+        assert_eq!(leaderboard.insert(entry("BBB", 300)), Some(0));
+        assert_eq!(leaderboard.insert(entry("CCC", 200)), Some(1));
+        // Equal scores go after the one already there
+        assert_eq!(leaderboard.insert(entry("DDD", 200)), Some(2));
+        let names: Vec<&str> = leaderboard
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["BBB", "CCC", "DDD", "AAA"]);
+
+        // Once full, only scores better than the last make it and the last drops off
+        for score in 0..LEADERBOARD_SIZE as u32 {
+            leaderboard.insert(entry("EEE", 1000 + score));
+        }
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert!(!leaderboard.qualifies(1000));
+        assert_eq!(leaderboard.insert(entry("FFF", 1000)), None);
+        assert!(leaderboard.qualifies(1001));
+        assert_eq!(leaderboard.insert(entry("GGG", 5000)), Some(0));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries[LEADERBOARD_SIZE - 1].score, 1001);
+    }
+
+    #[test]
+    fn format_date_gives_the_utc_day() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(86_399), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31");
+        assert_eq!(format_date(1_735_689_600), "2025-01-01");
+    }
+}
@@ -0,0 +1,167 @@
+use macroquad::prelude::*;
+
+use crate::hitbox::Hitbox;
+use crate::weapon::WeaponKind;
+use crate::world::Shape;
+
+// Chance a destroyed enemy drops a pickup, bosses always drop one. WEAPON_DROP_CHANCE of the pickups are
+// weapons rather than power-ups
+pub const DROP_CHANCE: f32 = 0.08;
+pub const WEAPON_DROP_CHANCE: f32 = 0.3;
+// How fast pickups drift down the play area, and how big they are
+const DRIFT_SPEED: f32 = 60.0;
+const PICKUP_SIZE: f32 = 28.0;
+
+// Score multiplier power-up multiplies points by this much
+pub const SCORE_MULTIPLIER: u32 = 2;
+
+// Power Up Kind Enum - What a pickup does while it lasts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUpKind {
+    // Three bullets fanned out with every shot
+    SpreadShot,
+    // Keeps firing while fire is held
+    RapidFire,
+    // A bubble that soaks up hits
+    Shield,
+    // More points for everything destroyed
+    ScoreMultiplier,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::SpreadShot,
+        PowerUpKind::RapidFire,
+        PowerUpKind::Shield,
+        PowerUpKind::ScoreMultiplier,
+    ];
+
+    // Duration function - Seconds the power-up lasts once picked up
+    pub fn duration(&self) -> f32 {
+        match self {
+            PowerUpKind::SpreadShot => 10.0,
+            PowerUpKind::RapidFire => 8.0,
+            PowerUpKind::Shield => 6.0,
+            PowerUpKind::ScoreMultiplier => 12.0,
+        }
+    }
+
+    // Name shown in the HUD
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::SpreadShot => "Spread Shot",
+            PowerUpKind::RapidFire => "Rapid Fire",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::ScoreMultiplier => "Score x2",
+        }
+    }
+}
+
+// Pickup Enum - What a power-up pickup gives the player: a timed power-up, or a weapon (another level of
+// the one they have, or a different weapon at the same level)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pickup {
+    PowerUp(PowerUpKind),
+    Weapon(WeaponKind),
+}
+
+// Power Up Struct - A pickup drifting down from where an enemy was destroyed
+pub struct PowerUp {
+    pub shape: Shape,
+    pub pickup: Pickup,
+}
+
+impl PowerUp {
+    pub fn new(pickup: Pickup, position: Vec2) -> PowerUp {
+        PowerUp {
+            shape: Shape {
+                size: PICKUP_SIZE,
+                speed: DRIFT_SPEED,
+                x: position.x,
+                y: position.y,
+                prev_x: position.x,
+                prev_y: position.y,
+                collided: false,
+                hitbox: Hitbox::Circle { radius: 0.5 },
+            },
+            pickup,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.shape.y += self.shape.speed * delta_time;
+    }
+}
+
+// Active Power Ups Struct - Seconds left of each power-up, 0.0 for the ones that aren't active
+#[derive(Default)]
+pub struct ActivePowerUps {
+    timers: [f32; PowerUpKind::ALL.len()],
+}
+
+impl ActivePowerUps {
+    pub fn update(&mut self, delta_time: f32) {
+        for timer in &mut self.timers {
+            *timer = (*timer - delta_time).max(0.0);
+        }
+    }
+
+    // Activate function - Starts a power-up, picking up one that's already active starts it over
+    pub fn activate(&mut self, kind: PowerUpKind) {
+        self.timers[kind as usize] = kind.duration();
+    }
+
+    pub fn active(&self, kind: PowerUpKind) -> bool {
+        self.time_left(kind) > 0.0
+    }
+
+    pub fn time_left(&self, kind: PowerUpKind) -> f32 {
+        self.timers[kind as usize]
+    }
+
+    // Clear function - Ends every power-up, they're lost with a life
+    pub fn clear(&mut self) {
+        self.timers = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_run_out() {
+        let mut active = ActivePowerUps::default();
+        assert!(PowerUpKind::ALL.iter().all(|&kind| !active.active(kind)));
+
+        active.activate(PowerUpKind::Shield);
+        active.update(1.0);
+        assert!(active.active(PowerUpKind::Shield));
+        assert_eq!(
+            active.time_left(PowerUpKind::Shield),
+            PowerUpKind::Shield.duration() - 1.0
+        );
+        assert!(!active.active(PowerUpKind::RapidFire));
+
+        // Picking it up again starts it over
+        active.activate(PowerUpKind::Shield);
+        assert_eq!(
+            active.time_left(PowerUpKind::Shield),
+            PowerUpKind::Shield.duration()
+        );
+
+        active.update(PowerUpKind::Shield.duration() + 1.0);
+        assert!(!active.active(PowerUpKind::Shield));
+        assert_eq!(active.time_left(PowerUpKind::Shield), 0.0);
+    }
+
+    #[test]
+    fn clearing_ends_every_power_up() {
+        let mut active = ActivePowerUps::default();
+        for kind in PowerUpKind::ALL {
+            active.activate(kind);
+        }
+        active.clear();
+        assert!(PowerUpKind::ALL.iter().all(|&kind| !active.active(kind)));
+    }
+}
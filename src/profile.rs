@@ -0,0 +1,480 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::KeyCode;
+
+use crate::input::{key_from_name, key_name, Action, Bindings};
+use crate::leaderboard::{Leaderboard, ScoreEntry};
+use crate::stats::Stats;
+
+// Profile file layout - one "key = value" per line, starting with the version:
+//   version = 1
+//   high_score.endless = 1234
+//   setting.music_volume = 0.5
+//   stat.runs = 12
+//   unlock = level-5
+//   bind.fire = Space K
+//   leaderboard = ACE 1234 3 1700000000 42    (name, score, level, date, seed)
+// Unknown keys are ignored so older builds can still read newer settings. A profile with a newer version
+// is left alone, older builds play on defaults without saving rather than overwrite it
+const VERSION: u32 = 1;
+const FILE_NAME: &str = "profile.sav";
+
+// High score file used before profiles existed, read once to migrate it
+const LEGACY_HIGHSCORE_FILE: &str = "highscore.dat";
+
+// Game modes with their own high score
+pub const MODE_ENDLESS: &str = "endless";
+
+// Profile Struct - Everything saved between runs
+pub struct Profile {
+    pub high_scores: BTreeMap<String, u32>,
+    pub settings: Settings,
+    pub stats: LifetimeStats,
+    pub unlocks: BTreeSet<String>,
+    pub leaderboard: Leaderboard,
+    pub bindings: Bindings,
+    path: PathBuf,
+    // Set for a profile from a newer version of the game, save does nothing so the file is kept as it is
+    read_only: bool,
+}
+
+// Settings Struct - Volumes, shake and particle density go from 0.0 to 1.0
+#[derive(Clone, Copy)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+    pub fullscreen: bool,
+    // Only read when the window is created, so changes apply after a restart
+    pub vsync: bool,
+    pub screen_shake: f32,
+    pub particle_density: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 0.4,
+            muted: false,
+            fullscreen: false,
+            vsync: true,
+            screen_shake: 1.0,
+            particle_density: 1.0,
+        }
+    }
+}
+
+// Lifetime Stats Struct - Stats added up over every run
+#[derive(Clone, Copy, Default)]
+pub struct LifetimeStats {
+    pub runs: u32,
+    pub shots_fired: u32,
+    pub enemies_destroyed: u32,
+    pub best_level: u32,
+}
+
+impl Profile {
+    // New function - An empty profile that will be saved to path
+    pub fn new(path: PathBuf) -> Profile {
+        Profile {
+            high_scores: BTreeMap::new(),
+            settings: Settings::default(),
+            stats: LifetimeStats::default(),
+            unlocks: BTreeSet::new(),
+            leaderboard: Leaderboard::default(),
+            bindings: Bindings::default(),
+            path,
+            read_only: false,
+        }
+    }
+
+    // Load or default function - Loads the profile from the data directory
+    pub fn load_or_default() -> Profile {
+        Profile::load_or_new(data_dir().join(FILE_NAME))
+    }
+
+    // Load or new function - A missing profile is migrated from highscore.dat, a corrupt one is moved
+    // aside so the player can keep playing, and one from a newer version is left alone
+    fn load_or_new(path: PathBuf) -> Profile {
+        match Profile::load(&path) {
+            Ok(profile) => profile,
+            Err(ProfileError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                let mut profile = Profile::new(path);
+                if let Some(high_score) = read_legacy_high_score() {
+                    profile
+                        .high_scores
+                        .insert(MODE_ENDLESS.to_string(), high_score);
+                    if let Err(error) = profile.save() {
+                        eprintln!("Could not save migrated profile: {}", error);
+                    }
+                }
+                profile
+            }
+            Err(error @ ProfileError::TooNew(_)) => {
+                eprintln!("Could not load profile, playing without saving: {}", error);
+                let mut profile = Profile::new(path);
+                profile.read_only = true;
+                profile
+            }
+            Err(error) => {
+                eprintln!("Could not load profile, starting a new one: {}", error);
+                let backup = path.with_extension("sav.corrupt");
+                if let Err(error) = fs::rename(&path, &backup) {
+                    eprintln!("Could not move corrupt profile aside: {}", error);
+                }
+                Profile::new(path)
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Profile, ProfileError> {
+        let text = fs::read_to_string(path)?;
+        let mut profile = Profile::new(path.to_path_buf());
+
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let version: u32 = match lines.next().and_then(split_line) {
+            Some(("version", value)) => parse(value)?,
+            _ => return Err(ProfileError::Corrupt("missing version".to_string())),
+        };
+        if version > VERSION {
+            return Err(ProfileError::TooNew(version));
+        }
+
+        let defaults = Settings::default();
+        for line in lines {
+            let (key, value) = split_line(line)
+                .ok_or_else(|| ProfileError::Corrupt(format!("bad line \"{}\"", line)))?;
+            match key {
+                "setting.master_volume" => {
+                    profile.settings.master_volume = parse_fraction(value, defaults.master_volume)?
+                }
+                "setting.music_volume" => {
+                    profile.settings.music_volume = parse_fraction(value, defaults.music_volume)?
+                }
+                "setting.sfx_volume" => {
+                    profile.settings.sfx_volume = parse_fraction(value, defaults.sfx_volume)?
+                }
+                "setting.muted" => profile.settings.muted = parse(value)?,
+                "setting.fullscreen" => profile.settings.fullscreen = parse(value)?,
+                "setting.vsync" => profile.settings.vsync = parse(value)?,
+                "setting.screen_shake" => {
+                    profile.settings.screen_shake = parse_fraction(value, defaults.screen_shake)?
+                }
+                "setting.particle_density" => {
+                    profile.settings.particle_density =
+                        parse_fraction(value, defaults.particle_density)?
+                }
+                "stat.runs" => profile.stats.runs = parse(value)?,
+                "stat.shots_fired" => profile.stats.shots_fired = parse(value)?,
+                "stat.enemies_destroyed" => profile.stats.enemies_destroyed = parse(value)?,
+                "stat.best_level" => profile.stats.best_level = parse(value)?,
+                "unlock" => {
+                    profile.unlocks.insert(value.to_string());
+                }
+                "leaderboard" => {
+                    profile.leaderboard.insert(parse_score_entry(value)?);
+                }
+                _ => {
+                    if let Some(mode) = key.strip_prefix("high_score.") {
+                        profile.high_scores.insert(mode.to_string(), parse(value)?);
+                    } else if let Some(action) = key.strip_prefix("bind.").and_then(Action::from_id)
+                    {
+                        // Keys that would leave Confirm or Back without one of their own keep the defaults,
+                        // and so does a binding left without any keys this game knows
+                        let keys = parse_keys(value);
+                        if !keys.is_empty() {
+                            profile.bindings.set(action, keys);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(profile)
+    }
+
+    // Save function - Writes to a temporary file and renames it over the profile, so a crash
+    // halfway through saving can never leave a half written profile behind
+    pub fn save(&self) -> Result<(), ProfileError> {
+        if self.read_only {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let temp_path = self.path.with_extension("sav.tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(self.to_text().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub fn high_score(&self, mode: &str) -> u32 {
+        self.high_scores.get(mode).copied().unwrap_or(0)
+    }
+
+    // Record run function - Adds a finished run's score and stats to the profile
+    pub fn record_run(&mut self, mode: &str, score: u32, stats: &Stats) {
+        let high_score = self.high_scores.entry(mode.to_string()).or_insert(0);
+        *high_score = (*high_score).max(score);
+
+        self.stats.runs += 1;
+        self.stats.shots_fired += stats.shots_fired;
+        self.stats.enemies_destroyed += stats.enemies_destroyed;
+        self.stats.best_level = self.stats.best_level.max(stats.level_reached);
+
+        // Unlock a milestone every 5 levels
+        for level in (5..=stats.level_reached).step_by(5) {
+            self.unlocks.insert(format!("level-{}", level));
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("version = {}\n", VERSION);
+        for (mode, score) in &self.high_scores {
+            text += &format!("high_score.{} = {}\n", mode, score);
+        }
+        text += &format!("setting.master_volume = {}\n", self.settings.master_volume);
+        text += &format!("setting.music_volume = {}\n", self.settings.music_volume);
+        text += &format!("setting.sfx_volume = {}\n", self.settings.sfx_volume);
+        text += &format!("setting.muted = {}\n", self.settings.muted);
+        text += &format!("setting.fullscreen = {}\n", self.settings.fullscreen);
+        text += &format!("setting.vsync = {}\n", self.settings.vsync);
+        text += &format!("setting.screen_shake = {}\n", self.settings.screen_shake);
+        text += &format!(
+            "setting.particle_density = {}\n",
+            self.settings.particle_density
+        );
+        text += &format!("stat.runs = {}\n", self.stats.runs);
+        text += &format!("stat.shots_fired = {}\n", self.stats.shots_fired);
+        text += &format!(
+            "stat.enemies_destroyed = {}\n",
+            self.stats.enemies_destroyed
+        );
+        text += &format!("stat.best_level = {}\n", self.stats.best_level);
+        for unlock in &self.unlocks {
+            text += &format!("unlock = {}\n", unlock);
+        }
+        for entry in &self.leaderboard.entries {
+            text += &format!(
+                "leaderboard = {} {} {} {} {}\n",
+                entry.name, entry.score, entry.level, entry.date, entry.seed
+            );
+        }
+        for action in Action::ALL {
+            let keys: Vec<&str> = self
+                .bindings
+                .keys(action)
+                .iter()
+                .filter_map(|&key| key_name(key))
+                .collect();
+            text += &format!("bind.{} = {}\n", action.id(), keys.join(" "));
+        }
+        text
+    }
+}
+
+// Profile Error Enum
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Corrupt(String),
+    TooNew(u32),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Io(error) => write!(f, "could not access profile: {}", error),
+            ProfileError::Corrupt(reason) => write!(f, "profile is corrupt: {}", reason),
+            ProfileError::TooNew(version) => {
+                write!(f, "profile version {} is newer than this game", version)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(error: io::Error) -> Self {
+        ProfileError::Io(error)
+    }
+}
+
+// Data dir function - Where the profile lives, following each platform's convention
+// ($XDG_DATA_HOME or ~/.local/share on Linux), falling back to the current directory
+fn data_dir() -> PathBuf {
+    let env_dir = |name: &str| {
+        std::env::var_os(name)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    };
+
+    base.map(|dir| dir.join("space-shooter"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn read_legacy_high_score() -> Option<u32> {
+    fs::read_to_string(LEGACY_HIGHSCORE_FILE)
+        .ok()
+        .and_then(|text| text.trim().parse().ok())
+}
+
+fn split_line(line: &str) -> Option<(&str, &str)> {
+    line.split_once('=')
+        .map(|(key, value)| (key.trim(), value.trim()))
+}
+
+fn parse_score_entry(value: &str) -> Result<ScoreEntry, ProfileError> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let [name, score, level, date, seed] = fields[..] else {
+        return Err(ProfileError::Corrupt(format!(
+            "bad leaderboard entry \"{}\"",
+            value
+        )));
+    };
+
+    Ok(ScoreEntry {
+        name: name.to_string(),
+        score: parse(score)?,
+        level: parse(level)?,
+        date: parse(date)?,
+        seed: parse(seed)?,
+    })
+}
+
+// Parse keys function - Skips key names that aren't known, a profile from another version of the game
+// shouldn't lose everything over one key
+fn parse_keys(value: &str) -> Vec<KeyCode> {
+    value.split_whitespace().filter_map(key_from_name).collect()
+}
+
+// Parse fraction function - Settings that go from 0.0 to 1.0, kept in range whatever the file says
+fn parse_fraction(value: &str, default: f32) -> Result<f32, ProfileError> {
+    let fraction: f32 = parse(value)?;
+    if fraction.is_finite() {
+        Ok(fraction.clamp(0.0, 1.0))
+    } else {
+        Ok(default)
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, ProfileError> {
+    value
+        .parse()
+        .map_err(|_| ProfileError::Corrupt(format!("bad value \"{}\"", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Temp path function - A profile path of its own for each test, with nothing left from earlier runs
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("space-shooter-test-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(FILE_NAME)
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("round-trip");
+        let mut profile = Profile::new(path.clone());
+        profile.high_scores.insert(MODE_ENDLESS.to_string(), 4200);
+        profile.settings.music_volume = 0.25;
+        profile.settings.muted = true;
+        profile.stats.runs = 12;
+        profile.stats.best_level = 7;
+        profile.unlocks.insert("level-5".to_string());
+        profile.leaderboard.insert(ScoreEntry {
+            name: "ZED".to_string(),
+            score: 4200,
+            level: 7,
+            date: 1_700_000_000,
+            seed: 99,
+        });
+        profile
+            .bindings
+            .set(Action::Fire, vec![KeyCode::X, KeyCode::Z]);
+        profile.save().unwrap();
+
+        let loaded = Profile::load(&path).unwrap();
+        assert_eq!(loaded.to_text(), profile.to_text());
+        assert_eq!(loaded.high_score(MODE_ENDLESS), 4200);
+        assert!(loaded.settings.muted);
+        assert_eq!(loaded.leaderboard.entries[0].name, "ZED");
+        assert_eq!(loaded.bindings.keys(Action::Fire), [KeyCode::X, KeyCode::Z]);
+    }
+
+    #[test]
+    fn newer_profile_is_left_alone() {
+        let path = temp_path("newer-profile");
+        let text = format!(
+            "version = {}\nhigh_score.endless = 900\nsetting.new = 1\n",
+            VERSION + 1
+        );
+        fs::write(&path, &text).unwrap();
+
+        assert!(matches!(Profile::load(&path), Err(ProfileError::TooNew(_))));
+        let mut profile = Profile::load_or_new(path.clone());
+        assert_eq!(profile.high_score(MODE_ENDLESS), 0);
+        profile.high_scores.insert(MODE_ENDLESS.to_string(), 100);
+        profile.save().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), text);
+        assert!(!path.with_extension("sav.corrupt").exists());
+    }
+
+    #[test]
+    fn unknown_keys_are_skipped() {
+        let path = temp_path("unknown-keys");
+        let text = format!(
+            "version = {}\nhigh_score.endless = 900\nbind.fire = X Warp\nbind.pause = Warp\n",
+            VERSION
+        );
+        fs::write(&path, text).unwrap();
+
+        let profile = Profile::load(&path).unwrap();
+        assert_eq!(profile.high_score(MODE_ENDLESS), 900);
+        assert_eq!(profile.bindings.keys(Action::Fire), [KeyCode::X]);
+        assert_eq!(
+            profile.bindings.keys(Action::Pause),
+            Bindings::default().keys(Action::Pause)
+        );
+    }
+
+    #[test]
+    fn settings_are_kept_in_range() {
+        let path = temp_path("settings-range");
+        let text = format!(
+            "version = {}\nsetting.master_volume = 7\nsetting.music_volume = -1\nsetting.sfx_volume = NaN\nsetting.screen_shake = inf\nsetting.particle_density = 0.3\n",
+            VERSION
+        );
+        fs::write(&path, text).unwrap();
+
+        let settings = Profile::load(&path).unwrap().settings;
+        let defaults = Settings::default();
+        assert_eq!(settings.master_volume, 1.0);
+        assert_eq!(settings.music_volume, 0.0);
+        assert_eq!(settings.sfx_volume, defaults.sfx_volume);
+        assert_eq!(settings.screen_shake, defaults.screen_shake);
+        assert_eq!(settings.particle_density, 0.3);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::world::{Input, PLAY_HEIGHT, PLAY_WIDTH};
+use std::fmt;
+use std::fs;
+use std::io;
+
+// Replay file layout (all numbers little endian):
+//   "SSRP" magic, u8 version, u64 seed, f32 width, f32 height, u32 tick count,
+//   then runs of (u8 input bits, i8 stick x, i8 stick y, u16 tick count) until every tick is covered
+const MAGIC: &[u8; 4] = b"SSRP";
+// Bump whenever World::step changes, old replays can't be played back the same way
+const VERSION: u8 = 13;
+const HEADER_SIZE: usize = 4 + 1 + 8 + 4 + 4 + 4;
+const RUN_SIZE: usize = 1 + 1 + 1 + 2;
+
+// Input bits
+const UP: u8 = 1 << 0;
+const DOWN: u8 = 1 << 1;
+const LEFT: u8 = 1 << 2;
+const RIGHT: u8 = 1 << 3;
+const FIRE: u8 = 1 << 4;
+const FIRE_HELD: u8 = 1 << 5;
+
+// Replay Struct - Everything needed to play a run back through World::step
+pub struct Replay {
+    pub seed: u64,
+    pub width: f32,
+    pub height: f32,
+    pub inputs: Vec<Input>,
+}
+
+impl Replay {
+    // New function - Starts an empty recording for a world of this seed and size
+    pub fn new(seed: u64, width: f32, height: f32) -> Replay {
+        Replay {
+            seed,
+            width,
+            height,
+            inputs: vec![],
+        }
+    }
+
+    // Record function - Call once per world step with the input that step received
+    pub fn record(&mut self, input: Input) {
+        self.inputs.push(input);
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Replay, ReplayError> {
+        Replay::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&(self.inputs.len() as u32).to_le_bytes());
+
+        // Held keys rarely change between ticks, so store each input once with how many ticks it lasted
+        let mut inputs = self.inputs.iter().map(|input| input_to_bytes(*input));
+        if let Some(first) = inputs.next() {
+            let mut current = (first, 1u16);
+            for input in inputs {
+                if input == current.0 && current.1 < u16::MAX {
+                    current.1 += 1;
+                } else {
+                    push_run(&mut bytes, current);
+                    current = (input, 1);
+                }
+            }
+            push_run(&mut bytes, current);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        if bytes[4] != VERSION {
+            return Err(ReplayError::UnsupportedVersion(bytes[4]));
+        }
+
+        let seed = u64::from_le_bytes(bytes[5..13].try_into().unwrap());
+        let width = f32::from_le_bytes(bytes[13..17].try_into().unwrap());
+        let height = f32::from_le_bytes(bytes[17..21].try_into().unwrap());
+        let ticks = u32::from_le_bytes(bytes[21..25].try_into().unwrap()) as usize;
+
+        // Every run is played in the same play area, anything else was damaged and would break the world
+        if width != PLAY_WIDTH || height != PLAY_HEIGHT {
+            return Err(ReplayError::Corrupt);
+        }
+
+        // The tick count comes from the file, so it's checked against the runs before anything is
+        // allocated for it
+        let runs = &bytes[HEADER_SIZE..];
+        if !runs.len().is_multiple_of(RUN_SIZE) {
+            return Err(ReplayError::Truncated);
+        }
+        let run_ticks: usize = runs
+            .chunks(RUN_SIZE)
+            .map(|run| u16::from_le_bytes([run[3], run[4]]) as usize)
+            .sum();
+        if run_ticks < ticks {
+            return Err(ReplayError::Truncated);
+        }
+        if run_ticks > ticks {
+            return Err(ReplayError::Corrupt);
+        }
+
+        let mut inputs = Vec::with_capacity(ticks);
+        for run in runs.chunks(RUN_SIZE) {
+            let input = bytes_to_input([run[0], run[1], run[2]]);
+            let count = u16::from_le_bytes([run[3], run[4]]);
+            inputs.extend(std::iter::repeat_n(input, count as usize));
+        }
+
+        Ok(Replay {
+            seed,
+            width,
+            height,
+            inputs,
+        })
+    }
+}
+
+// Playback Struct - Hands out a replay's inputs one step at a time
+pub struct Playback {
+    pub replay: Replay,
+    tick: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback { replay, tick: 0 }
+    }
+
+    // Next input function - None once every recorded step has been played
+    pub fn next_input(&mut self) -> Option<Input> {
+        let input = self.replay.inputs.get(self.tick).copied();
+        self.tick += 1;
+        input
+    }
+}
+
+// Replay Error Enum
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    NotAReplay,
+    UnsupportedVersion(u8),
+    Truncated,
+    Corrupt,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "could not access replay file: {}", error),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(f, "unsupported replay version {}", version)
+            }
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::Corrupt => write!(f, "replay file is corrupt"),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+fn push_run(bytes: &mut Vec<u8>, (input, count): ([u8; 3], u16)) {
+    bytes.extend_from_slice(&input);
+    bytes.extend_from_slice(&count.to_le_bytes());
+}
+
+// Input to bytes function - The key bits followed by the two stick axes
+fn input_to_bytes(input: Input) -> [u8; 3] {
+    let mut bits = 0;
+    if input.up {
+        bits |= UP;
+    }
+    if input.down {
+        bits |= DOWN;
+    }
+    if input.left {
+        bits |= LEFT;
+    }
+    if input.right {
+        bits |= RIGHT;
+    }
+    if input.fire {
+        bits |= FIRE;
+    }
+    if input.fire_held {
+        bits |= FIRE_HELD;
+    }
+    [bits, input.stick_x as u8, input.stick_y as u8]
+}
+
+fn bytes_to_input([bits, stick_x, stick_y]: [u8; 3]) -> Input {
+    Input {
+        up: bits & UP != 0,
+        down: bits & DOWN != 0,
+        left: bits & LEFT != 0,
+        right: bits & RIGHT != 0,
+        fire: bits & FIRE != 0,
+        fire_held: bits & FIRE_HELD != 0,
+        stick_x: stick_x as i8,
+        stick_y: stick_y as i8,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> Replay {
+        let mut replay = Replay::new(42, PLAY_WIDTH, PLAY_HEIGHT);
+        for tick in 0..500 {
+            replay.record(Input {
+                left: tick < 200,
+                fire: tick % 7 == 0,
+                fire_held: tick > 300,
+                stick_x: (tick % 50) as i8 - 25,
+                stick_y: -127,
+                ..Default::default()
+            });
+        }
+        replay
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = recording();
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!((loaded.width, loaded.height), (replay.width, replay.height));
+        assert_eq!(loaded.inputs.len(), replay.inputs.len());
+        for (a, b) in loaded.inputs.iter().zip(&replay.inputs) {
+            assert_eq!(input_to_bytes(*a), input_to_bytes(*b));
+        }
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let bytes = recording().to_bytes();
+        assert!(matches!(
+            Replay::from_bytes(b"nope"),
+            Err(ReplayError::NotAReplay)
+        ));
+
+        let mut old = bytes.clone();
+        old[4] = VERSION - 1;
+        assert!(matches!(
+            Replay::from_bytes(&old),
+            Err(ReplayError::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            Replay::from_bytes(&bytes[..bytes.len() - 2]),
+            Err(ReplayError::Truncated)
+        ));
+
+        // A tick count far past what the runs hold is turned down rather than allocated for
+        let mut huge = bytes.clone();
+        huge[21..25].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Replay::from_bytes(&huge),
+            Err(ReplayError::Truncated)
+        ));
+
+        // A play area of another size, or no size at all
+        for size in [0.0, f32::NAN, 1.0, PLAY_WIDTH * 2.0] {
+            let mut resized = bytes.clone();
+            resized[13..17].copy_from_slice(&size.to_le_bytes());
+            assert!(matches!(
+                Replay::from_bytes(&resized),
+                Err(ReplayError::Corrupt)
+            ));
+        }
+
+        let mut short = bytes;
+        short[21..25].copy_from_slice(&1u32.to_le_bytes());
+        assert!(matches!(
+            Replay::from_bytes(&short),
+            Err(ReplayError::Corrupt)
+        ));
+    }
+}
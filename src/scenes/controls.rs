@@ -0,0 +1,158 @@
+use macroquad::prelude::*;
+
+use super::menu::MenuFocus;
+use super::{Context, Scene, Transition};
+use crate::input::{key_name, Action, Bindings};
+
+// Cancels rebinding a key, so it can't be bound itself (Reset to defaults puts it back on Pause and Back)
+const CANCEL_KEY: KeyCode = KeyCode::Escape;
+
+// Controls Scene Struct - Lists every action's keys and lets the player rebind them
+pub struct ControlsScene {
+    // Row being edited, one per action plus "Reset to defaults" at the end
+    menu: MenuFocus,
+    // Waiting for the key to bind to the selected action
+    listening: bool,
+    // The last key pressed would have left Confirm or Back without a key of their own
+    refused: bool,
+}
+
+impl ControlsScene {
+    pub fn new() -> ControlsScene {
+        ControlsScene {
+            menu: MenuFocus::new(Action::ALL.len() + 1),
+            listening: false,
+            refused: false,
+        }
+    }
+}
+
+impl Scene for ControlsScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // The next bindable key pressed replaces the action's keys, Escape (or back on a gamepad) cancels
+        if self.listening {
+            if is_key_pressed(CANCEL_KEY) || context.gamepads.is_pressed(Action::Back) {
+                self.listening = false;
+                return Transition::None;
+            }
+            let key = get_keys_pressed()
+                .into_iter()
+                .find(|&key| key_name(key).is_some());
+            if let Some(key) = key {
+                self.refused = !context
+                    .profile
+                    .bindings
+                    .set(Action::ALL[self.menu.focused], vec![key]);
+                self.listening = false;
+            }
+            return Transition::None;
+        }
+
+        // Move between rows, confirm rebinds the row's action or resets them all
+        if let Some(row) = self.menu.update(context) {
+            self.refused = false;
+            if row < Action::ALL.len() {
+                self.listening = true;
+            } else {
+                context.profile.bindings = Bindings::default();
+            }
+        }
+
+        // Save the bindings and return to Options
+        if context.is_pressed(Action::Back) {
+            if let Err(error) = context.profile.save() {
+                eprintln!("Could not save profile: {}", error);
+            }
+            return Transition::Pop;
+        }
+
+        Transition::None
+    }
+
+    fn captures_keys(&self) -> bool {
+        self.listening
+    }
+
+    fn draw(&mut self, context: &mut Context) {
+        let bindings = &context.profile.bindings;
+
+        // Display "Controls" text
+        let text = "CONTROLS";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            80.0,
+            50.0,
+            WHITE,
+        );
+
+        // One row per action: its name and the keys bound to it
+        let left = screen_width() / 2.0 - 220.0;
+        for (index, action) in Action::ALL.into_iter().enumerate() {
+            let y = 150.0 + index as f32 * 36.0;
+            let selected = index == self.menu.focused;
+            let color = if selected { YELLOW } else { WHITE };
+            let keys = if selected && self.listening {
+                "Press a key...".to_string()
+            } else {
+                key_list(bindings, action)
+            };
+            draw_text(action.label(), left, y, 30.0, color);
+            draw_text(keys.as_str(), left + 220.0, y, 30.0, color);
+        }
+
+        let reset_color = if self.menu.focused == Action::ALL.len() {
+            YELLOW
+        } else {
+            WHITE
+        };
+        draw_text(
+            "Reset to defaults",
+            left,
+            150.0 + Action::ALL.len() as f32 * 36.0 + 18.0,
+            30.0,
+            reset_color,
+        );
+
+        if self.refused {
+            let text = "Confirm and Back each need a key of their own";
+            let text_dimensions = measure_text(text, None, 25, 1.0);
+            draw_text(
+                text,
+                screen_width() / 2.0 - text_dimensions.width / 2.0,
+                screen_height() - 70.0,
+                25.0,
+                RED,
+            );
+        }
+
+        // Say which keys to use, as they may have been rebound
+        let text = if self.listening {
+            "Escape to cancel".to_string()
+        } else {
+            format!(
+                "{} to change   {} to return",
+                key_list(bindings, Action::Confirm),
+                key_list(bindings, Action::Back)
+            )
+        };
+        let text_dimensions = measure_text(text.as_str(), None, 25, 1.0);
+        draw_text(
+            text.as_str(),
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() - 35.0,
+            25.0,
+            WHITE,
+        );
+    }
+}
+
+fn key_list(bindings: &Bindings, action: Action) -> String {
+    bindings
+        .keys(action)
+        .iter()
+        .filter_map(|&key| key_name(key))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
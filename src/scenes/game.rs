@@ -0,0 +1,546 @@
+use macroquad::experimental::animation::{AnimatedSprite, Animation};
+use macroquad::prelude::*;
+
+use super::game_over::GameOverScene;
+use super::pause::PauseScene;
+use super::{Context, Scene, Transition};
+use crate::audio::{MusicState, SoundEffects};
+use crate::events::dispatch;
+use crate::hud::{pickup_look, power_up_look, Hud};
+use crate::input::Action;
+use crate::leaderboard::ScoreEntry;
+use crate::powerup::{Pickup, PowerUpKind};
+use crate::profile::MODE_ENDLESS;
+use crate::replay::{Playback, Replay};
+use crate::stats::Stats;
+use crate::vfx::{Explosions, ScreenShake};
+use crate::weapon::WeaponKind;
+use crate::world::{Input, SizeClass, World, PLAY_HEIGHT, PLAY_WIDTH, TIME_STEP};
+use crate::{new_seed, REPLAY_FILE};
+
+// Longest frame time simulated in one go, so a hitch doesn't cause a burst of catch-up steps
+const MAX_FRAME_TIME: f32 = 0.25;
+
+// Times per second the player switches between shown and hidden while invulnerable
+const PLAYER_BLINK_RATE: f32 = 10.0;
+
+// Tint for an enemy that was just hit but not destroyed
+const HIT_FLASH_COLOR: Color = Color::new(1.0, 0.35, 0.35, 1.0);
+
+// Game Scene Struct - A run being played (or a replay being watched)
+pub struct GameScene {
+    world: World,
+
+    // Systems listening to the world's events
+    sound_effects: SoundEffects,
+    explosions: Explosions,
+    screen_shake: ScreenShake,
+    hud: Hud,
+    stats: Stats,
+
+    // Frame time not yet simulated, a Space press waiting for the next step, and the input of the last step
+    accumulator: f32,
+    fire_pending: bool,
+    last_input: Input,
+
+    // Recording of the current run, and the replay being played back (if any)
+    recording: Replay,
+    playback: Option<Playback>,
+
+    // Animations
+    enemy_small_sprite: AnimatedSprite,
+    enemy_medium_sprite: AnimatedSprite,
+    enemy_large_sprite: AnimatedSprite,
+    // One for each weapon, in WeaponKind::ALL order
+    weapon_sprites: Vec<AnimatedSprite>,
+    bolt_sprite: AnimatedSprite,
+    player_sprite: AnimatedSprite,
+}
+
+impl GameScene {
+    // New run function - Starts a new run in the fixed size play area
+    pub fn new_run(context: &Context) -> GameScene {
+        let world = World::new(
+            PLAY_WIDTH,
+            PLAY_HEIGHT,
+            new_seed(context.seed_arg),
+            context.resources.hitboxes.clone(),
+            context.resources.waves.clone(),
+        );
+        GameScene::new(context, world, None)
+    }
+
+    // New replay function - Plays back a recorded run
+    pub fn new_replay(context: &Context, replay: Replay) -> GameScene {
+        let world = World::new(
+            replay.width,
+            replay.height,
+            replay.seed,
+            context.resources.hitboxes.clone(),
+            context.resources.waves.clone(),
+        );
+        GameScene::new(context, world, Some(Playback::new(replay)))
+    }
+
+    fn new(context: &Context, world: World, playback: Option<Playback>) -> GameScene {
+        let resources = context.resources;
+
+        // Create animations
+        let enemy_small_sprite = AnimatedSprite::new(
+            17,
+            16,
+            &[Animation {
+                name: "enemy_small".to_string(),
+                row: 0,
+                frames: 2,
+                fps: 12,
+            }],
+            true,
+        );
+
+        let enemy_medium_sprite = AnimatedSprite::new(
+            32,
+            16,
+            &[Animation {
+                name: "enemy_medium".to_string(),
+                row: 0,
+                frames: 2,
+                fps: 12,
+            }],
+            true,
+        );
+
+        let enemy_large_sprite = AnimatedSprite::new(
+            32,
+            32,
+            &[Animation {
+                name: "enemy_large".to_string(),
+                row: 0,
+                frames: 2,
+                fps: 12,
+            }],
+            true,
+        );
+
+        let weapon_sprites = WeaponKind::ALL
+            .iter()
+            .map(|kind| {
+                let sprite = kind.sprite();
+                AnimatedSprite::new(
+                    16,
+                    16,
+                    &[Animation {
+                        name: kind.name().to_string(),
+                        row: sprite.row,
+                        frames: 2,
+                        fps: sprite.fps,
+                    }],
+                    true,
+                )
+            })
+            .collect();
+
+        // Enemy bolts, the second row of the same sheet
+        let bolt_sprite = AnimatedSprite::new(
+            16,
+            16,
+            &[Animation {
+                name: "bolt".to_string(),
+                row: 1,
+                frames: 2,
+                fps: 12,
+            }],
+            true,
+        );
+
+        let player_sprite = AnimatedSprite::new(
+            16,
+            24,
+            &[
+                Animation {
+                    name: "idle".to_string(),
+                    row: 0,
+                    frames: 2,
+                    fps: 12,
+                },
+                Animation {
+                    name: "left".to_string(),
+                    row: 2,
+                    frames: 2,
+                    fps: 12,
+                },
+                Animation {
+                    name: "right".to_string(),
+                    row: 4,
+                    frames: 2,
+                    fps: 12,
+                },
+            ],
+            true,
+        );
+
+        GameScene {
+            recording: Replay::new(world.seed, world.width, world.height),
+            world,
+            sound_effects: SoundEffects::default(),
+            explosions: Explosions::new(
+                resources.explosion_texture.clone(),
+                context.profile.settings.particle_density,
+            ),
+            screen_shake: ScreenShake::default(),
+            hud: Hud::default(),
+            stats: Stats::new(),
+            accumulator: 0.0,
+            fire_pending: false,
+            last_input: Input::default(),
+            playback,
+            enemy_small_sprite,
+            enemy_medium_sprite,
+            enemy_large_sprite,
+            weapon_sprites,
+            bolt_sprite,
+            player_sprite,
+        }
+    }
+
+    // Game over function - Saves the score, stats and recording of a live run, then shows Game Over.
+    // A score good enough for the leaderboard is passed on so the player can enter their name
+    fn game_over(&self, context: &mut Context) -> Transition {
+        let mut new_entry = None;
+        if self.playback.is_none() {
+            if context.profile.leaderboard.qualifies(self.world.score) {
+                new_entry = Some(ScoreEntry {
+                    name: String::new(),
+                    score: self.world.score,
+                    level: self.world.level,
+                    date: miniquad::date::now() as u64,
+                    seed: self.world.seed,
+                });
+            }
+            context
+                .profile
+                .record_run(MODE_ENDLESS, self.world.score, &self.stats);
+            if let Err(error) = context.profile.save() {
+                eprintln!("Could not save profile: {}", error);
+            }
+            if let Err(error) = self.recording.save(REPLAY_FILE) {
+                eprintln!("Could not save replay: {}", error);
+            }
+        }
+        Transition::Replace(Box::new(GameOverScene::new(
+            self.world.seed,
+            self.stats,
+            new_entry,
+        )))
+    }
+}
+
+impl Scene for GameScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // Get delta time for visuals only, the world itself is stepped at a fixed rate
+        let delta_time = get_frame_time();
+
+        // The music builds up with the level
+        context.mixer.set_level(self.world.level);
+
+        // Particle density may have been changed from the pause menu
+        self.explosions
+            .set_density(context.profile.settings.particle_density);
+
+        // Handle keys
+        let mut input = read_input(context);
+        self.fire_pending |= input.fire;
+        if context.is_pressed(Action::Pause) {
+            return Transition::Push(Box::new(PauseScene::new()));
+        }
+
+        // Step the world as many times as the frame time allows (capped so a long hitch doesn't stall the game)
+        self.accumulator += delta_time.min(MAX_FRAME_TIME);
+        while self.accumulator >= TIME_STEP {
+            self.accumulator -= TIME_STEP;
+
+            // Take the step's input from the replay, or from the keyboard while recording it
+            let step_input = match self.playback.as_mut() {
+                Some(playback) => match playback.next_input() {
+                    Some(step_input) => step_input,
+                    None => return self.game_over(context),
+                },
+                None => {
+                    input.fire = self.fire_pending;
+                    self.fire_pending = false;
+                    self.recording.record(input);
+                    input
+                }
+            };
+            self.last_input = step_input;
+
+            // Step the world and let every system react to what happened
+            let events = self.world.step(TIME_STEP, step_input);
+            dispatch(
+                events,
+                &mut [
+                    &mut self.sound_effects,
+                    &mut self.explosions,
+                    &mut self.screen_shake,
+                    &mut self.hud,
+                    &mut self.stats,
+                ],
+            );
+            self.sound_effects.play(&mut context.mixer);
+
+            // Check for game over
+            if self.world.game_over {
+                return self.game_over(context);
+            }
+        }
+
+        //Set player animation
+        self.player_sprite.set_animation(0);
+        if self.last_input.left || self.last_input.stick_x < 0 {
+            context.direction_modifier -= 5.0 * delta_time;
+            self.player_sprite.set_animation(1);
+        }
+        if self.last_input.right || self.last_input.stick_x > 0 {
+            context.direction_modifier += 5.0 * delta_time;
+            self.player_sprite.set_animation(2);
+        }
+
+        // Update sprites
+        self.enemy_small_sprite.update();
+        self.enemy_medium_sprite.update();
+        self.enemy_large_sprite.update();
+        for sprite in &mut self.weapon_sprites {
+            sprite.update();
+        }
+        self.bolt_sprite.update();
+        self.player_sprite.update();
+
+        self.explosions.update();
+        self.screen_shake
+            .update(delta_time, context.profile.settings.screen_shake);
+        self.hud.update(delta_time);
+
+        Transition::None
+    }
+
+    fn on_enter(&mut self, context: &mut Context) {
+        context.mixer.set_level(self.world.level);
+        context.mixer.set_music(MusicState::Gameplay);
+    }
+
+    fn draw(&mut self, context: &mut Context) {
+        let resources = context.resources;
+
+        // How far we are between the last step and the next one, used to interpolate drawing
+        let alpha = self.accumulator / TIME_STEP;
+
+        // Everything but the HUD is moved by the screen shake
+        let shake = self.screen_shake.offset;
+
+        // The world is drawn at its own size, scaled to fit the window
+        set_camera(&play_area_camera(self.world.width, self.world.height));
+
+        // Draw explosions
+        self.explosions.draw(shake);
+
+        // Draw enemies
+        let enemy_small_frame = self.enemy_small_sprite.frame();
+        let enemy_medium_frame = self.enemy_medium_sprite.frame();
+        let enemy_large_frame = self.enemy_large_sprite.frame();
+        for enemy in &self.world.enemies {
+            let shape = &enemy.shape;
+            let position = shape.lerp_position(alpha) + shake;
+            let (texture, frame) = match enemy.kind.size_class() {
+                SizeClass::Small => (&resources.enemy_small_texture, &enemy_small_frame),
+                SizeClass::Medium => (&resources.enemy_medium_texture, &enemy_medium_frame),
+                SizeClass::Large => (&resources.enemy_large_texture, &enemy_large_frame),
+            };
+            let color = if enemy.hit_flash > 0.0 {
+                HIT_FLASH_COLOR
+            } else {
+                WHITE
+            };
+            draw_texture_ex(
+                texture,
+                position.x - shape.size / 2.0,
+                position.y - shape.size / 2.0,
+                color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(shape.size, shape.size)),
+                    source: Some(frame.source_rect),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Draw the boss, its core with the large enemy sprite and its turrets with the medium one
+        if let Some(boss) = &self.world.boss {
+            for (index, part) in boss.parts.iter().enumerate() {
+                let shape = &part.shape;
+                let position = shape.lerp_position(alpha) + shake;
+                let (texture, frame) = if index == 0 {
+                    (&resources.enemy_large_texture, &enemy_large_frame)
+                } else {
+                    (&resources.enemy_medium_texture, &enemy_medium_frame)
+                };
+                let color = if part.hit_flash > 0.0 {
+                    HIT_FLASH_COLOR
+                } else {
+                    WHITE
+                };
+                draw_texture_ex(
+                    texture,
+                    position.x - shape.size / 2.0,
+                    position.y - shape.size / 2.0,
+                    color,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(shape.size, shape.size)),
+                        source: Some(frame.source_rect),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        // Draw pickups, a ring (a square for weapons) in the pickup's colour with its letter in the middle
+        for power_up in &self.world.power_ups {
+            let shape = &power_up.shape;
+            let position = shape.lerp_position(alpha) + shake;
+            let (color, letter) = pickup_look(power_up.pickup);
+            let radius = shape.size / 2.0;
+            if let Pickup::Weapon(_) = power_up.pickup {
+                let (x, y) = (position.x - radius, position.y - radius);
+                draw_rectangle(x, y, shape.size, shape.size, Color { a: 0.3, ..color });
+                draw_rectangle_lines(x, y, shape.size, shape.size, 2.0, color);
+            } else {
+                draw_circle(position.x, position.y, radius, Color { a: 0.3, ..color });
+                draw_circle_lines(position.x, position.y, radius, 2.0, color);
+            }
+            let text_dimensions = measure_text(letter, None, 20, 1.0);
+            draw_text(
+                letter,
+                position.x - text_dimensions.width / 2.0,
+                position.y + text_dimensions.offset_y / 2.0,
+                20.0,
+                WHITE,
+            );
+        }
+
+        // Draw the player's projectiles with their weapon's sprite, turned to the way they're flying
+        let weapon_frames: Vec<_> = self
+            .weapon_sprites
+            .iter()
+            .map(|sprite| sprite.frame())
+            .collect();
+        for bullet in &self.world.bullets {
+            let Some(weapon) = bullet.weapon else {
+                continue;
+            };
+            let shape = &bullet.shape;
+            let position = shape.lerp_position(alpha) + shake;
+            draw_texture_ex(
+                &resources.bullet_texture,
+                position.x - shape.size / 2.0,
+                position.y - shape.size / 2.0,
+                weapon.sprite().color,
+                DrawTextureParams {
+                    dest_size: Some(vec2(shape.size, shape.size)),
+                    source: Some(weapon_frames[weapon as usize].source_rect),
+                    rotation: bullet.velocity.x.atan2(-bullet.velocity.y),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Draw enemy bolts
+        let bolt_frame = self.bolt_sprite.frame();
+        for bullet in &self.world.enemy_bullets {
+            let shape = &bullet.shape;
+            let position = shape.lerp_position(alpha) + shake;
+            draw_texture_ex(
+                &resources.bullet_texture,
+                position.x - shape.size / 2.0,
+                position.y - shape.size / 2.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(shape.size, shape.size)),
+                    source: Some(bolt_frame.source_rect),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Draw player, hidden while waiting to respawn and blinking while invulnerable
+        let blink = (self.world.invulnerable_timer * PLAYER_BLINK_RATE) as u32 % 2 == 1;
+        if self.world.player_alive() && !blink {
+            let player_frame = self.player_sprite.frame();
+            let player_position = self.world.player.lerp_position(alpha) + shake;
+            draw_texture_ex(
+                &resources.player_texture,
+                player_position.x - player_frame.dest_size.x,
+                player_position.y - player_frame.dest_size.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(player_frame.dest_size * 2.0),
+                    source: Some(player_frame.source_rect),
+                    ..Default::default()
+                },
+            );
+
+            // The shield bubble flickers in its last second
+            let shield = self.world.active_power_ups.time_left(PowerUpKind::Shield);
+            let flicker = shield < 1.0 && (shield * PLAYER_BLINK_RATE) as u32 % 2 == 1;
+            if shield > 0.0 && !flicker {
+                let (color, _) = power_up_look(PowerUpKind::Shield);
+                let radius = self.world.player.size * 1.2;
+                draw_circle(
+                    player_position.x,
+                    player_position.y,
+                    radius,
+                    Color { a: 0.2, ..color },
+                );
+                draw_circle_lines(player_position.x, player_position.y, radius, 2.0, color);
+            }
+        }
+
+        // Draw scores and banners over the whole window
+        set_default_camera();
+        let mut high_score = context.profile.high_score(MODE_ENDLESS);
+        if self.playback.is_none() {
+            high_score = high_score.max(self.world.score);
+        }
+        self.hud
+            .draw(&self.world, high_score, self.playback.is_some());
+    }
+}
+
+// Play area camera function - Maps the play area onto the biggest part of the window with the same
+// shape, centred with bars either side or above and below
+fn play_area_camera(width: f32, height: f32) -> Camera2D {
+    let scale = (screen_width() / width).min(screen_height() / height);
+    let (view_width, view_height) = (width * scale, height * scale);
+    // Flipped so y goes down the screen, as it does without a camera
+    let mut camera = Camera2D::from_display_rect(Rect::new(0.0, height, width, -height));
+    camera.viewport = Some((
+        ((screen_width() - view_width) / 2.0) as i32,
+        ((screen_height() - view_height) / 2.0) as i32,
+        view_width as i32,
+        view_height as i32,
+    ));
+    camera
+}
+
+// Read input function - Maps the bound keys and gamepad to the world's Input
+fn read_input(context: &Context) -> Input {
+    let stick = context.gamepads.stick();
+    Input {
+        up: context.is_down(Action::MoveUp),
+        down: context.is_down(Action::MoveDown),
+        left: context.is_down(Action::MoveLeft),
+        right: context.is_down(Action::MoveRight),
+        fire: context.is_pressed(Action::Fire),
+        fire_held: context.is_down(Action::Fire),
+        stick_x: (stick.x * 127.0).round() as i8,
+        stick_y: (stick.y * 127.0).round() as i8,
+    }
+}
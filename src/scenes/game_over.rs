@@ -0,0 +1,102 @@
+use macroquad::prelude::*;
+
+use super::main_menu::MainMenuScene;
+use super::name_entry::NameEntryScene;
+use super::{Context, Scene, Transition};
+use crate::audio::MusicState;
+use crate::input::Action;
+use crate::leaderboard::ScoreEntry;
+use crate::stats::Stats;
+
+// Seconds before confirm or back is taken, so buttons still being hammered from the run don't skip
+// past the screen (A is fire and confirm on a gamepad)
+const INPUT_DELAY: f32 = 1.0;
+
+// Game Over Scene Struct
+pub struct GameOverScene {
+    seed: u64,
+    stats: Stats,
+    // Leaderboard entry still waiting for a name
+    new_entry: Option<ScoreEntry>,
+    // Seconds since the run ended
+    time: f32,
+}
+
+impl GameOverScene {
+    // New function - seed and stats are from the run that just ended
+    pub fn new(seed: u64, stats: Stats, new_entry: Option<ScoreEntry>) -> GameOverScene {
+        GameOverScene {
+            seed,
+            stats,
+            new_entry,
+            time: 0.0,
+        }
+    }
+}
+
+impl Scene for GameOverScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // Press confirm (or back) to enter a name for a new high score, or to return to Main Menu
+        self.time += get_frame_time();
+        if self.time < INPUT_DELAY {
+            return Transition::None;
+        }
+        if context.is_pressed(Action::Confirm) || context.is_pressed(Action::Back) {
+            return match self.new_entry.take() {
+                Some(entry) => Transition::Replace(Box::new(NameEntryScene::new(entry))),
+                None => Transition::Replace(Box::new(MainMenuScene::new())),
+            };
+        }
+
+        Transition::None
+    }
+
+    fn on_enter(&mut self, context: &mut Context) {
+        context.mixer.set_music(MusicState::GameOver);
+    }
+
+    // On exit function - Stops the sting, it shouldn't carry on over the next screen
+    fn on_exit(&mut self, context: &mut Context) {
+        context.mixer.set_music(MusicState::Silent);
+    }
+
+    fn draw(&mut self, _context: &mut Context) {
+        // Display "Game Over" text
+        let text = "GAME OVER!";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 2.0,
+            50.0,
+            RED,
+        );
+
+        // Display the seed so the run can be replayed with --seed
+        let seed_text = format!("Seed: {}", self.seed);
+        let text_dimensions = measure_text(seed_text.as_str(), None, 25, 1.0);
+        draw_text(
+            seed_text.as_str(),
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 2.0 + 40.0,
+            25.0,
+            WHITE,
+        );
+
+        // Display the run's stats
+        let stats_text = format!(
+            "Level {}   Destroyed {}   Accuracy {}%",
+            self.stats.level_reached,
+            self.stats.enemies_destroyed,
+            self.stats.accuracy()
+        );
+        let text_dimensions = measure_text(stats_text.as_str(), None, 25, 1.0);
+        draw_text(
+            stats_text.as_str(),
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 2.0 + 70.0,
+            25.0,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use macroquad::prelude::*;
+
+use super::main_menu::MainMenuScene;
+use super::{Context, Scene, Transition};
+use crate::audio::MusicState;
+use crate::input::Action;
+use crate::leaderboard::format_date;
+
+// High Scores Scene Struct - Lists the leaderboard
+pub struct HighScoresScene {
+    // Position of a score that was just added, so it can be highlighted
+    highlight: Option<usize>,
+}
+
+impl HighScoresScene {
+    pub fn new(highlight: Option<usize>) -> HighScoresScene {
+        HighScoresScene { highlight }
+    }
+}
+
+impl Scene for HighScoresScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // Press confirm or back to return to Main Menu
+        if context.is_pressed(Action::Confirm) || context.is_pressed(Action::Back) {
+            return Transition::Replace(Box::new(MainMenuScene::new()));
+        }
+
+        Transition::None
+    }
+
+    fn on_enter(&mut self, context: &mut Context) {
+        context.mixer.set_music(MusicState::Menu);
+    }
+
+    fn draw(&mut self, context: &mut Context) {
+        // Display "High Scores" text
+        let text = "HIGH SCORES";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            80.0,
+            50.0,
+            WHITE,
+        );
+
+        let entries = &context.profile.leaderboard.entries;
+        let left = screen_width() / 2.0 - 300.0;
+        if entries.is_empty() {
+            let text = "No scores yet";
+            let text_dimensions = measure_text(text, None, 30, 1.0);
+            draw_text(
+                text,
+                screen_width() / 2.0 - text_dimensions.width / 2.0,
+                160.0,
+                30.0,
+                WHITE,
+            );
+        } else {
+            draw_text(
+                "    NAME   SCORE  LEVEL  DATE        SEED",
+                left,
+                140.0,
+                25.0,
+                GRAY,
+            );
+        }
+
+        // One row per entry: rank, name, score, level, date and seed
+        for (index, entry) in entries.iter().enumerate() {
+            let row = format!(
+                "{:>2}. {:<4} {:>7} {:>6}  {}  {}",
+                index + 1,
+                entry.name,
+                entry.score,
+                entry.level,
+                format_date(entry.date),
+                entry.seed
+            );
+            let color = if self.highlight == Some(index) {
+                YELLOW
+            } else {
+                WHITE
+            };
+            draw_text(row.as_str(), left, 175.0 + index as f32 * 32.0, 25.0, color);
+        }
+
+        let text = "Press Enter to return";
+        let text_dimensions = measure_text(text, None, 25, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() - 35.0,
+            25.0,
+            WHITE,
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui};
+
+use super::game::GameScene;
+use super::high_scores::HighScoresScene;
+use super::menu::MenuFocus;
+use super::options::OptionsScene;
+use super::{Context, Scene, Transition};
+use crate::audio::MusicState;
+use crate::input::Action;
+use crate::replay::{Replay, ReplayError};
+use crate::REPLAY_FILE;
+
+// Menu Item Enum - The main menu's buttons, top to bottom
+#[derive(Clone, Copy)]
+enum MenuItem {
+    Play,
+    Scores,
+    Replay,
+    Options,
+    Quit,
+}
+
+const MENU_ITEMS: [MenuItem; 5] = [
+    MenuItem::Play,
+    MenuItem::Scores,
+    MenuItem::Replay,
+    MenuItem::Options,
+    MenuItem::Quit,
+];
+
+impl MenuItem {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuItem::Play => "Play",
+            MenuItem::Scores => "Scores",
+            MenuItem::Replay => "Replay",
+            MenuItem::Options => "Options",
+            MenuItem::Quit => "Quit",
+        }
+    }
+}
+
+// Main Menu Scene Struct
+pub struct MainMenuScene {
+    replay_error: Option<ReplayError>,
+    // Button chosen with the keyboard or gamepad, activated with confirm
+    menu: MenuFocus,
+}
+
+impl MainMenuScene {
+    pub fn new() -> MainMenuScene {
+        MainMenuScene {
+            replay_error: None,
+            menu: MenuFocus::new(MENU_ITEMS.len()),
+        }
+    }
+
+    // Activate function - Does what a menu button is for, whether it was clicked or confirmed
+    fn activate(&mut self, item: MenuItem, context: &mut Context) -> Transition {
+        match item {
+            MenuItem::Play => Transition::Replace(Box::new(GameScene::new_run(context))),
+            MenuItem::Scores => Transition::Replace(Box::new(HighScoresScene::new(None))),
+            MenuItem::Replay => match Replay::load(REPLAY_FILE) {
+                Ok(replay) => Transition::Replace(Box::new(GameScene::new_replay(context, replay))),
+                Err(error) => {
+                    self.replay_error = Some(error);
+                    Transition::None
+                }
+            },
+            MenuItem::Options => Transition::Push(Box::new(OptionsScene::new())),
+            MenuItem::Quit => Transition::Quit,
+        }
+    }
+}
+
+impl Scene for MainMenuScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        let window_size = vec2(400.0, 560.0);
+
+        // Move the focus with up/down (keys, d-pad), confirm presses the focused button and back goes to Quit
+        let mut chosen = self.menu.update(context).map(|index| MENU_ITEMS[index]);
+        if context.is_pressed(Action::Back) {
+            self.menu.focused = MENU_ITEMS.len() - 1;
+        }
+
+        // Create and display the menu (macroquad's UI is drawn at the end of the frame, so this can live in update)
+        let focused = self.menu.focused;
+        root_ui().window(
+            hash!(),
+            vec2(
+                screen_width() / 2.0 - window_size.x / 2.0,
+                screen_height() / 2.0 - window_size.y / 2.0,
+            ),
+            window_size,
+            |ui| {
+                ui.label(vec2(80.0, -34.0), "Main Menu");
+                for (index, item) in MENU_ITEMS.into_iter().enumerate() {
+                    let position = vec2(65.0, 25.0 + index as f32 * 85.0);
+                    if index == focused {
+                        ui.label(vec2(25.0, position.y + 20.0), ">");
+                    }
+                    if ui.button(position, item.label()) {
+                        chosen = Some(item);
+                    }
+                }
+            },
+        );
+
+        match chosen {
+            Some(item) => self.activate(item, context),
+            None => Transition::None,
+        }
+    }
+
+    fn on_enter(&mut self, context: &mut Context) {
+        context.mixer.set_music(MusicState::Menu);
+    }
+
+    fn draw(&mut self, _context: &mut Context) {
+        // Explain why the replay couldn't be played
+        if let Some(error) = &self.replay_error {
+            let text = format!("Replay unavailable: {}", error);
+            let text_dimensions = measure_text(text.as_str(), None, 25, 1.0);
+            draw_text(
+                text.as_str(),
+                screen_width() / 2.0 - text_dimensions.width / 2.0,
+                screen_height() - 35.0,
+                25.0,
+                RED,
+            );
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use macroquad::prelude::*;
+
+use super::Context;
+use crate::input::Action;
+
+// Menu Focus Struct - Which item of a menu the keyboard or gamepad is on
+pub struct MenuFocus {
+    pub focused: usize,
+    items: usize,
+}
+
+impl MenuFocus {
+    pub fn new(items: usize) -> MenuFocus {
+        MenuFocus { focused: 0, items }
+    }
+
+    // Update function - Moves the focus up and down (wrapping around), returns the focused item when it is confirmed
+    pub fn update(&mut self, context: &Context) -> Option<usize> {
+        if context.is_pressed(Action::MoveUp) {
+            self.focused = (self.focused + self.items - 1) % self.items;
+        }
+        if context.is_pressed(Action::MoveDown) {
+            self.focused = (self.focused + 1) % self.items;
+        }
+
+        if context.is_pressed(Action::Confirm) {
+            Some(self.focused)
+        } else {
+            None
+        }
+    }
+}
+
+// Draw menu function - Draws the items centred under each other from y, highlighting the focused one
+pub fn draw_menu(labels: &[&str], focused: usize, y: f32) {
+    for (index, label) in labels.iter().enumerate() {
+        let text = if index == focused {
+            format!("> {} <", label)
+        } else {
+            label.to_string()
+        };
+        let color = if index == focused { YELLOW } else { WHITE };
+        let text_dimensions = measure_text(text.as_str(), None, 40, 1.0);
+        draw_text(
+            text.as_str(),
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            y + index as f32 * 50.0,
+            40.0,
+            color,
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::audio::Mixer;
+use crate::gamepad::Gamepads;
+use crate::input::Action;
+use crate::profile::Profile;
+use crate::Resources;
+
+pub mod controls;
+pub mod game;
+pub mod game_over;
+pub mod high_scores;
+pub mod main_menu;
+pub mod menu;
+pub mod name_entry;
+pub mod options;
+pub mod pause;
+
+// Context Struct - State shared by every scene
+pub struct Context {
+    pub resources: &'static Resources,
+    pub seed_arg: Option<u64>,
+    pub profile: Profile,
+    pub gamepads: Gamepads,
+    pub mixer: Mixer,
+    // Drives the starfield shader, scenes nudge it to make the stars drift
+    pub direction_modifier: f32,
+}
+
+impl Context {
+    // Is down function - Whether an action is held on the keyboard or a gamepad
+    pub fn is_down(&self, action: Action) -> bool {
+        self.profile.bindings.is_down(action) || self.gamepads.is_down(action)
+    }
+
+    // Is pressed function - Whether an action was pressed this frame on the keyboard or a gamepad
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.profile.bindings.is_pressed(action) || self.gamepads.is_pressed(action)
+    }
+}
+
+// Transition Enum - What a scene wants done to the stack after its update
+pub enum Transition<C = Context> {
+    None,
+    // Put a scene on top, the current one stays underneath (e.g. pause)
+    Push(Box<dyn Scene<C>>),
+    // Remove the current scene, going back to the one underneath
+    Pop,
+    // Swap the current scene for another one (e.g. main menu to game)
+    Replace(Box<dyn Scene<C>>),
+    // Remove every scene and start again from this one (e.g. pause to main menu)
+    Clear(Box<dyn Scene<C>>),
+    Quit,
+}
+
+// Scene Trait - A screen of the game. Only the top scene is updated. Every scene of the game uses the
+// shared Context, the stack itself works with any context so it can be tested without a window
+pub trait Scene<C = Context> {
+    fn update(&mut self, context: &mut C) -> Transition<C>;
+
+    fn draw(&mut self, context: &mut C);
+
+    // Called when the scene is added to the stack
+    fn on_enter(&mut self, _context: &mut C) {}
+
+    // Called when the scene is removed from the stack
+    fn on_exit(&mut self, _context: &mut C) {}
+
+    // Overlays are drawn on top of the scene underneath instead of replacing it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    // Whether the scene wants every key to itself (e.g. while rebinding), so global keys like mute are ignored
+    fn captures_keys(&self) -> bool {
+        false
+    }
+}
+
+// Scene Stack Struct
+pub struct SceneStack<C = Context> {
+    scenes: Vec<Box<dyn Scene<C>>>,
+}
+
+impl<C> SceneStack<C> {
+    pub fn new(mut first: Box<dyn Scene<C>>, context: &mut C) -> SceneStack<C> {
+        first.on_enter(context);
+        SceneStack {
+            scenes: vec![first],
+        }
+    }
+
+    // Update function - Updates the top scene and applies its transition. Returns false when the game should quit
+    pub fn update(&mut self, context: &mut C) -> bool {
+        let Some(top) = self.scenes.last_mut() else {
+            return false;
+        };
+
+        match top.update(context) {
+            Transition::None => {}
+            Transition::Push(scene) => self.push(scene, context),
+            Transition::Pop => self.pop(context),
+            Transition::Replace(scene) => {
+                self.pop(context);
+                self.push(scene, context);
+            }
+            Transition::Clear(scene) => {
+                while !self.scenes.is_empty() {
+                    self.pop(context);
+                }
+                self.push(scene, context);
+            }
+            Transition::Quit => return false,
+        }
+
+        !self.scenes.is_empty()
+    }
+
+    // Captures keys function - Whether the top scene wants every key to itself
+    pub fn captures_keys(&self) -> bool {
+        self.scenes
+            .last()
+            .is_some_and(|scene| scene.captures_keys())
+    }
+
+    // Draw function - Draws the top scene, and the scenes underneath it while it is an overlay
+    pub fn draw(&mut self, context: &mut C) {
+        let first_drawn = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+
+        for scene in &mut self.scenes[first_drawn..] {
+            scene.draw(context);
+        }
+    }
+
+    fn push(&mut self, mut scene: Box<dyn Scene<C>>, context: &mut C) {
+        scene.on_enter(context);
+        self.scenes.push(scene);
+    }
+
+    fn pop(&mut self, context: &mut C) {
+        if let Some(mut scene) = self.scenes.pop() {
+            scene.on_exit(context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Log = Vec<String>;
+
+    // Test Scene Struct - Records what the stack does to it and hands back one scripted transition
+    struct TestScene {
+        name: &'static str,
+        overlay: bool,
+        next: Option<Transition<Log>>,
+    }
+
+    impl Scene<Log> for TestScene {
+        fn update(&mut self, log: &mut Log) -> Transition<Log> {
+            log.push(format!("update {}", self.name));
+            self.next.take().unwrap_or(Transition::None)
+        }
+
+        fn draw(&mut self, log: &mut Log) {
+            log.push(format!("draw {}", self.name));
+        }
+
+        fn on_enter(&mut self, log: &mut Log) {
+            log.push(format!("enter {}", self.name));
+        }
+
+        fn on_exit(&mut self, log: &mut Log) {
+            log.push(format!("exit {}", self.name));
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+    }
+
+    fn scene(name: &'static str, overlay: bool, next: Transition<Log>) -> Box<TestScene> {
+        Box::new(TestScene {
+            name,
+            overlay,
+            next: Some(next),
+        })
+    }
+
+    #[test]
+    fn overlays_are_drawn_over_the_scene_underneath() {
+        let mut log = Log::new();
+        let pause = scene("pause", true, Transition::Pop);
+        let mut stack = SceneStack::new(scene("game", false, Transition::Push(pause)), &mut log);
+
+        assert!(stack.update(&mut log));
+        stack.draw(&mut log);
+        assert_eq!(
+            log,
+            [
+                "enter game",
+                "update game",
+                "enter pause",
+                "draw game",
+                "draw pause"
+            ]
+        );
+
+        // Only the top scene is updated, popping it goes back to the game
+        log.clear();
+        assert!(stack.update(&mut log));
+        stack.draw(&mut log);
+        assert_eq!(log, ["update pause", "exit pause", "draw game"]);
+    }
+
+    #[test]
+    fn scenes_under_a_full_screen_scene_are_not_drawn() {
+        let mut log = Log::new();
+        let options = scene("options", false, Transition::None);
+        let mut stack = SceneStack::new(scene("menu", false, Transition::Push(options)), &mut log);
+
+        stack.update(&mut log);
+        log.clear();
+        stack.draw(&mut log);
+        assert_eq!(log, ["draw options"]);
+    }
+
+    #[test]
+    fn replace_and_clear_exit_the_old_scenes_first() {
+        let mut log = Log::new();
+        let menu = scene("menu", false, Transition::None);
+        let pause = scene("pause", true, Transition::Clear(menu));
+        let game = scene("game", false, Transition::Push(pause));
+        let mut stack = SceneStack::new(scene("title", false, Transition::Replace(game)), &mut log);
+
+        stack.update(&mut log);
+        assert_eq!(
+            log,
+            ["enter title", "update title", "exit title", "enter game"]
+        );
+
+        stack.update(&mut log);
+        log.clear();
+        assert!(stack.update(&mut log));
+        assert_eq!(
+            log,
+            ["update pause", "exit pause", "exit game", "enter menu"]
+        );
+
+        log.clear();
+        stack.draw(&mut log);
+        assert_eq!(log, ["draw menu"]);
+    }
+
+    #[test]
+    fn quitting_or_popping_the_last_scene_ends_the_game() {
+        let mut log = Log::new();
+        let mut stack = SceneStack::new(scene("menu", false, Transition::Quit), &mut log);
+        assert!(!stack.update(&mut log));
+
+        let mut stack = SceneStack::new(scene("menu", false, Transition::Pop), &mut log);
+        assert!(!stack.update(&mut log));
+        assert!(!stack.update(&mut log));
+    }
+}
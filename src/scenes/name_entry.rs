@@ -0,0 +1,135 @@
+use macroquad::prelude::*;
+
+use super::high_scores::HighScoresScene;
+use super::{Context, Scene, Transition};
+use crate::input::Action;
+use crate::leaderboard::ScoreEntry;
+
+// Letters the name can be made of
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const NAME_LENGTH: usize = 3;
+
+// Name Entry Scene Struct - Arcade style three letter name entry for a new leaderboard score
+pub struct NameEntryScene {
+    entry: ScoreEntry,
+    // Index into ALPHABET of each letter
+    letters: [usize; NAME_LENGTH],
+    cursor: usize,
+}
+
+impl NameEntryScene {
+    // New function - entry is the score to add, its name is filled in here
+    pub fn new(entry: ScoreEntry) -> NameEntryScene {
+        NameEntryScene {
+            entry,
+            letters: [0; NAME_LENGTH],
+            cursor: 0,
+        }
+    }
+
+    fn name(&self) -> String {
+        self.letters
+            .iter()
+            .map(|&letter| ALPHABET[letter] as char)
+            .collect()
+    }
+}
+
+impl Scene for NameEntryScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // Up/down changes the letter, left/right (or back) moves between letters
+        let letter = &mut self.letters[self.cursor];
+        if context.is_pressed(Action::MoveUp) {
+            *letter = (*letter + ALPHABET.len() - 1) % ALPHABET.len();
+        }
+        if context.is_pressed(Action::MoveDown) {
+            *letter = (*letter + 1) % ALPHABET.len();
+        }
+        if (context.is_pressed(Action::MoveLeft) || context.is_pressed(Action::Back))
+            && self.cursor > 0
+        {
+            self.cursor -= 1;
+        }
+        if context.is_pressed(Action::MoveRight) {
+            self.cursor = (self.cursor + 1).min(NAME_LENGTH - 1);
+        }
+
+        // Confirm moves on to the next letter, the name is saved after the last one
+        if context.is_pressed(Action::Confirm) {
+            if self.cursor < NAME_LENGTH - 1 {
+                self.cursor += 1;
+            } else {
+                let mut entry = self.entry.clone();
+                entry.name = self.name();
+                let rank = context.profile.leaderboard.insert(entry);
+                if let Err(error) = context.profile.save() {
+                    eprintln!("Could not save profile: {}", error);
+                }
+                return Transition::Replace(Box::new(HighScoresScene::new(rank)));
+            }
+        }
+
+        Transition::None
+    }
+
+    fn draw(&mut self, _context: &mut Context) {
+        // Display "New High Score" text
+        let text = "NEW HIGH SCORE!";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 3.0,
+            50.0,
+            YELLOW,
+        );
+
+        let score_text = format!("{}", self.entry.score);
+        let text_dimensions = measure_text(score_text.as_str(), None, 40, 1.0);
+        draw_text(
+            score_text.as_str(),
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 3.0 + 50.0,
+            40.0,
+            WHITE,
+        );
+
+        // Draw the letters, underlining the one being changed
+        let letter_width = 60.0;
+        let left = screen_width() / 2.0 - letter_width * NAME_LENGTH as f32 / 2.0;
+        let baseline = screen_height() / 2.0 + 60.0;
+        for (index, &letter) in self.letters.iter().enumerate() {
+            let text = (ALPHABET[letter] as char).to_string();
+            let text_dimensions = measure_text(text.as_str(), None, 60, 1.0);
+            let x = left + letter_width * index as f32;
+            let color = if index == self.cursor { YELLOW } else { WHITE };
+            draw_text(
+                text.as_str(),
+                x + letter_width / 2.0 - text_dimensions.width / 2.0,
+                baseline,
+                60.0,
+                color,
+            );
+            if index == self.cursor {
+                draw_line(
+                    x + 8.0,
+                    baseline + 8.0,
+                    x + letter_width - 8.0,
+                    baseline + 8.0,
+                    4.0,
+                    YELLOW,
+                );
+            }
+        }
+
+        let text = "Up/Down to change letter, Enter to confirm";
+        let text_dimensions = measure_text(text, None, 25, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            baseline + 60.0,
+            25.0,
+            WHITE,
+        );
+    }
+}
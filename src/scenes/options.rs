@@ -0,0 +1,191 @@
+use macroquad::prelude::*;
+
+use super::controls::ControlsScene;
+use super::menu::MenuFocus;
+use super::{Context, Scene, Transition};
+use crate::input::Action;
+use crate::profile::Settings;
+
+// How much one press of left/right changes a slider
+const SLIDER_STEP: f32 = 0.1;
+
+// Option Item Enum - The rows of the options screen, top to bottom
+#[derive(Clone, Copy)]
+enum OptionItem {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Mute,
+    Fullscreen,
+    Vsync,
+    ScreenShake,
+    ParticleDensity,
+    Controls,
+    Back,
+}
+
+const OPTION_ITEMS: [OptionItem; 10] = [
+    OptionItem::MasterVolume,
+    OptionItem::MusicVolume,
+    OptionItem::SfxVolume,
+    OptionItem::Mute,
+    OptionItem::Fullscreen,
+    OptionItem::Vsync,
+    OptionItem::ScreenShake,
+    OptionItem::ParticleDensity,
+    OptionItem::Controls,
+    OptionItem::Back,
+];
+
+impl OptionItem {
+    fn label(&self) -> &'static str {
+        match self {
+            OptionItem::MasterVolume => "Volume",
+            OptionItem::MusicVolume => "Music Volume",
+            OptionItem::SfxVolume => "Sound Volume",
+            OptionItem::Mute => "Mute",
+            OptionItem::Fullscreen => "Fullscreen",
+            OptionItem::Vsync => "VSync",
+            OptionItem::ScreenShake => "Screen Shake",
+            OptionItem::ParticleDensity => "Particles",
+            OptionItem::Controls => "Controls",
+            OptionItem::Back => "Back",
+        }
+    }
+
+    // Value function - The setting as shown next to its label
+    fn value(&self, settings: &Settings) -> String {
+        let percent = |value: f32| format!("< {}% >", (value * 100.0).round());
+        let on_off = |value: bool| if value { "On" } else { "Off" }.to_string();
+        match self {
+            OptionItem::MasterVolume => percent(settings.master_volume),
+            OptionItem::MusicVolume => percent(settings.music_volume),
+            OptionItem::SfxVolume => percent(settings.sfx_volume),
+            OptionItem::Mute => on_off(settings.muted),
+            OptionItem::Fullscreen => on_off(settings.fullscreen),
+            OptionItem::Vsync => on_off(settings.vsync),
+            OptionItem::ScreenShake => percent(settings.screen_shake),
+            OptionItem::ParticleDensity => percent(settings.particle_density),
+            OptionItem::Controls | OptionItem::Back => String::new(),
+        }
+    }
+
+    // Slider function - The setting left/right changes, for the rows that are sliders
+    fn slider<'a>(&self, settings: &'a mut Settings) -> Option<&'a mut f32> {
+        match self {
+            OptionItem::MasterVolume => Some(&mut settings.master_volume),
+            OptionItem::MusicVolume => Some(&mut settings.music_volume),
+            OptionItem::SfxVolume => Some(&mut settings.sfx_volume),
+            OptionItem::ScreenShake => Some(&mut settings.screen_shake),
+            OptionItem::ParticleDensity => Some(&mut settings.particle_density),
+            _ => None,
+        }
+    }
+}
+
+// Options Scene Struct - Audio, display and gameplay settings. Pushed over the main menu or pause,
+// and popped to go back to them
+pub struct OptionsScene {
+    menu: MenuFocus,
+}
+
+impl OptionsScene {
+    pub fn new() -> OptionsScene {
+        OptionsScene {
+            menu: MenuFocus::new(OPTION_ITEMS.len()),
+        }
+    }
+
+    // Close function - Saves the settings and goes back to where options were opened from
+    fn close(&self, context: &mut Context) -> Transition {
+        if let Err(error) = context.profile.save() {
+            eprintln!("Could not save profile: {}", error);
+        }
+        Transition::Pop
+    }
+}
+
+impl Scene for OptionsScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        let confirmed = self.menu.update(context).is_some();
+        let item = OPTION_ITEMS[self.menu.focused];
+
+        // Left/right moves sliders and flips toggles, confirm flips toggles too
+        let mut step = 0.0;
+        if context.is_pressed(Action::MoveLeft) {
+            step -= SLIDER_STEP;
+        }
+        if context.is_pressed(Action::MoveRight) {
+            step += SLIDER_STEP;
+        }
+        let back = context.is_pressed(Action::Back);
+
+        let settings = &mut context.profile.settings;
+        if let Some(value) = item.slider(settings) {
+            // Rounded so repeated steps don't drift away from whole percentages
+            *value = ((*value + step).clamp(0.0, 1.0) * 10.0).round() / 10.0;
+        }
+
+        let toggled = confirmed || step != 0.0;
+        match item {
+            OptionItem::Mute if toggled => settings.muted = !settings.muted,
+            OptionItem::Fullscreen if toggled => {
+                settings.fullscreen = !settings.fullscreen;
+                set_fullscreen(settings.fullscreen);
+            }
+            OptionItem::Vsync if toggled => settings.vsync = !settings.vsync,
+            OptionItem::Controls if confirmed => {
+                return Transition::Push(Box::new(ControlsScene::new()))
+            }
+            OptionItem::Back if confirmed => return self.close(context),
+            _ => {}
+        }
+
+        // Hear volume changes straight away
+        context.mixer.apply_settings(&context.profile.settings);
+
+        if back {
+            return self.close(context);
+        }
+
+        Transition::None
+    }
+
+    fn draw(&mut self, context: &mut Context) {
+        let settings = &context.profile.settings;
+
+        // Display "Options" text
+        let text = "OPTIONS";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            80.0,
+            50.0,
+            WHITE,
+        );
+
+        // One row per setting: its name and value
+        let left = screen_width() / 2.0 - 220.0;
+        for (index, item) in OPTION_ITEMS.into_iter().enumerate() {
+            let y = 150.0 + index as f32 * 40.0;
+            let color = if index == self.menu.focused {
+                YELLOW
+            } else {
+                WHITE
+            };
+            draw_text(item.label(), left, y, 30.0, color);
+            draw_text(item.value(settings).as_str(), left + 260.0, y, 30.0, color);
+        }
+
+        let text = "VSync changes apply after restarting the game";
+        let text_dimensions = measure_text(text, None, 25, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() - 35.0,
+            25.0,
+            GRAY,
+        );
+    }
+}
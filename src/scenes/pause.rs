@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+use super::main_menu::MainMenuScene;
+use super::menu::{draw_menu, MenuFocus};
+use super::options::OptionsScene;
+use super::{Context, Scene, Transition};
+use crate::input::Action;
+
+const MENU_LABELS: [&str; 3] = ["Resume", "Options", "Main Menu"];
+
+// Pause Scene Struct - Drawn over the frozen game
+pub struct PauseScene {
+    menu: MenuFocus,
+}
+
+impl PauseScene {
+    pub fn new() -> PauseScene {
+        PauseScene {
+            menu: MenuFocus::new(MENU_LABELS.len()),
+        }
+    }
+}
+
+impl Scene for PauseScene {
+    fn update(&mut self, context: &mut Context) -> Transition {
+        // Press pause again (or back) to un-pause
+        if context.is_pressed(Action::Pause) || context.is_pressed(Action::Back) {
+            return Transition::Pop;
+        }
+
+        match self.menu.update(context) {
+            Some(0) => Transition::Pop,
+            Some(1) => Transition::Push(Box::new(OptionsScene::new())),
+            // Give up on the run
+            Some(_) => Transition::Clear(Box::new(MainMenuScene::new())),
+            None => Transition::None,
+        }
+    }
+
+    // Keep the music going quietly underneath
+    fn on_enter(&mut self, context: &mut Context) {
+        context.mixer.set_ducked(true);
+    }
+
+    fn on_exit(&mut self, context: &mut Context) {
+        context.mixer.set_ducked(false);
+    }
+
+    fn draw(&mut self, _context: &mut Context) {
+        // Dim the game underneath
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::new(0.0, 0.0, 0.0, 0.5),
+        );
+
+        // Display "Paused" text
+        let text = "Paused";
+        let text_dimensions = measure_text(text, None, 50, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2.0 - text_dimensions.width / 2.0,
+            screen_height() / 2.0,
+            50.0,
+            WHITE,
+        );
+
+        draw_menu(
+            &MENU_LABELS,
+            self.menu.focused,
+            screen_height() / 2.0 + 70.0,
+        );
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,149 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+// Size of the collision grid cells, the same as the largest enemy
+pub const GRID_CELL_SIZE: f32 = 64.0;
+
+// Spatial Hash Struct - Buckets entities by the grid cells their rect touches,
+// so collision checks only need to look at entities in nearby cells
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>, BuildHasherDefault<CellHasher>>,
+}
+
+impl SpatialHash {
+    // New function - cell_size should be around the size of the largest entity
+    pub fn new(cell_size: f32) -> SpatialHash {
+        SpatialHash {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    // Clear function - Empties every cell but keeps their memory for the next step
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    // Insert function - index is the entity's position in its Vec
+    pub fn insert(&mut self, index: usize, rect: Rect) {
+        let (min, max) = self.cell_range(rect);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    // Query function - Fills found with the index of every entity sharing a cell with rect, each listed once
+    pub fn query(&self, rect: Rect, found: &mut Vec<usize>) {
+        found.clear();
+        let (min, max) = self.cell_range(rect);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend_from_slice(cell);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+    }
+
+    fn cell_range(&self, rect: Rect) -> ((i32, i32), (i32, i32)) {
+        (
+            self.cell(rect.x, rect.y),
+            self.cell(rect.x + rect.w, rect.y + rect.h),
+        )
+    }
+
+    fn cell(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            (x / self.cell_size).floor() as i32,
+            (y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+// Cell Hasher Struct - Cell coordinates are small integers, so a multiply-and-rotate hash is plenty
+// and much faster than the default hasher for the many lookups each step
+#[derive(Default)]
+struct CellHasher(u64);
+
+impl Hasher for CellHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
+        }
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u64(i as u32 as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = (self.0.rotate_left(5) ^ i).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use macroquad::rand::RandGenerator;
+
+    #[test]
+    fn finds_the_same_pairs_as_checking_every_one() {
+        let rng = RandGenerator::new();
+        rng.srand(5);
+        // Sizes either side of the cell size, some of them reaching off the top left into negative cells
+        let random_rects = |count: usize| -> Vec<Rect> {
+            (0..count)
+                .map(|_| {
+                    let size = rng.gen_range(4.0, GRID_CELL_SIZE * 2.5);
+                    Rect::new(
+                        rng.gen_range(-100.0, 800.0),
+                        rng.gen_range(-100.0, 600.0),
+                        size,
+                        size * rng.gen_range(0.3, 1.0),
+                    )
+                })
+                .collect()
+        };
+        let enemies = random_rects(300);
+        let bullets = random_rects(300);
+
+        let mut grid = SpatialHash::new(GRID_CELL_SIZE);
+        for (index, rect) in enemies.iter().enumerate() {
+            grid.insert(index, *rect);
+        }
+
+        let mut nearby = vec![];
+        let mut pairs = 0;
+        for bullet in &bullets {
+            grid.query(*bullet, &mut nearby);
+            let overlapping: Vec<usize> = nearby
+                .iter()
+                .copied()
+                .filter(|&index| enemies[index].overlaps(bullet))
+                .collect();
+            let expected: Vec<usize> = (0..enemies.len())
+                .filter(|&index| enemies[index].overlaps(bullet))
+                .collect();
+            assert_eq!(overlapping, expected);
+            pairs += expected.len();
+        }
+        assert!(pairs > 0);
+
+        // Cleared, the grid finds nothing until it's filled again
+        grid.clear();
+        grid.query(bullets[0], &mut nearby);
+        assert!(nearby.is_empty());
+    }
+}
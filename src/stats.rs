@@ -0,0 +1,46 @@
+use crate::events::{EventListener, GameEvent};
+
+// Stats Struct - Counts what happened during a run
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    // Bullets fired, and the ones that hit an enemy whether or not it was destroyed
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub enemies_destroyed: u32,
+    pub hits_taken: u32,
+    pub level_reached: u32,
+}
+
+impl Stats {
+    // New function - Every run starts on level 1
+    pub fn new() -> Stats {
+        Stats {
+            level_reached: 1,
+            ..Default::default()
+        }
+    }
+
+    // Accuracy function - Percentage of bullets that hit an enemy
+    pub fn accuracy(&self) -> u32 {
+        if self.shots_fired == 0 {
+            return 0;
+        }
+        (self.shots_hit * 100) / self.shots_fired
+    }
+}
+
+impl EventListener for Stats {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::ShotFired { bullets, .. } => self.shots_fired += bullets,
+            GameEvent::EnemyHit { .. } => self.shots_hit += 1,
+            GameEvent::EnemyDestroyed { .. } => {
+                self.shots_hit += 1;
+                self.enemies_destroyed += 1;
+            }
+            GameEvent::PlayerHit { .. } => self.hits_taken += 1,
+            GameEvent::LevelUp { level } => self.level_reached = self.level_reached.max(*level),
+            _ => {}
+        }
+    }
+}
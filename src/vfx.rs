@@ -0,0 +1,126 @@
+use macroquad::prelude::*;
+use macroquad_particles::{self as particles, AtlasConfig, Emitter, EmitterConfig};
+
+use crate::events::{EventListener, GameEvent};
+
+// Furthest the screen moves at full shake, in pixels
+const MAX_SHAKE: f32 = 12.0;
+// Seconds a full shake takes to die down
+const SHAKE_DECAY: f32 = 0.6;
+
+// How big the explosion is when a boss is defeated, in the same units as an enemy's size
+const BOSS_EXPLOSION_SIZE: f32 = 160.0;
+// How big the spark is when a shot bounces off a boss's shield
+const DEFLECT_SPARK_SIZE: f32 = 4.0;
+
+// Explosions Struct - Particle explosions where enemies are destroyed
+pub struct Explosions {
+    texture: Texture2D,
+    emitters: Vec<(Emitter, Vec2)>,
+    // Particle density setting, scales how many particles each explosion has
+    density: f32,
+}
+
+impl Explosions {
+    pub fn new(texture: Texture2D, density: f32) -> Explosions {
+        Explosions {
+            texture,
+            emitters: vec![],
+            density,
+        }
+    }
+
+    pub fn set_density(&mut self, density: f32) {
+        self.density = density;
+    }
+
+    // Update function - Retain only explosions currently emitting, discard others
+    pub fn update(&mut self) {
+        self.emitters
+            .retain(|(explosion, _)| explosion.config.emitting);
+    }
+
+    // Draw function - offset moves every explosion, for screen shake
+    pub fn draw(&mut self, offset: Vec2) {
+        for (explosion, coords) in self.emitters.iter_mut() {
+            explosion.draw(*coords + offset);
+        }
+    }
+}
+
+impl EventListener for Explosions {
+    fn on_event(&mut self, event: &GameEvent) {
+        let (size, pos) = match *event {
+            GameEvent::EnemyDestroyed { size, pos } => (size, pos),
+            // The player's ship is 32 pixels across
+            GameEvent::PlayerHit { pos } => (32.0, pos),
+            GameEvent::BossDefeated { pos } => (BOSS_EXPLOSION_SIZE, pos),
+            GameEvent::ShotDeflected { pos } => (DEFLECT_SPARK_SIZE, pos),
+            _ => return,
+        };
+        // Anything bigger than the largest enemy gets bigger, longer lasting particles too
+        let scale = (size / 64.0).max(1.0);
+        let config = particle_explosion();
+        self.emitters.push((
+            Emitter::new(EmitterConfig {
+                amount: ((size.round() * 4.0 * self.density) as u32).max(1),
+                texture: Some(self.texture.clone()),
+                size: config.size * scale,
+                lifetime: config.lifetime * scale,
+                ..config
+            }),
+            pos,
+        ));
+    }
+}
+
+// Screen Shake Struct - Shakes the screen when things blow up. The shake grows with the square of
+// its trauma (0.0 to 1.0), so small explosions stay subtle and big hits really jolt
+#[derive(Default)]
+pub struct ScreenShake {
+    trauma: f32,
+    // How far to move the game this frame, picked in update so it holds still while paused
+    pub offset: Vec2,
+}
+
+impl ScreenShake {
+    // Update function - strength is the screen shake setting
+    pub fn update(&mut self, delta_time: f32, strength: f32) {
+        self.trauma = (self.trauma - delta_time / SHAKE_DECAY).max(0.0);
+        let amount = self.trauma * self.trauma * MAX_SHAKE * strength;
+        self.offset = vec2(rand::gen_range(-1.0, 1.0), rand::gen_range(-1.0, 1.0)) * amount;
+    }
+
+    fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+}
+
+impl EventListener for ScreenShake {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::EnemyDestroyed { size, .. } => self.add_trauma(size / 64.0 * 0.5),
+            GameEvent::PlayerHit { .. } | GameEvent::BossDefeated { .. } => self.add_trauma(1.0),
+            _ => {}
+        }
+    }
+}
+
+// Explosions function
+fn particle_explosion() -> particles::EmitterConfig {
+    particles::EmitterConfig {
+        local_coords: false,
+        one_shot: true,
+        emitting: true,
+        lifetime: 0.6,
+        lifetime_randomness: 0.3,
+        explosiveness: 0.65,
+        initial_direction_spread: 2.0 * std::f32::consts::PI,
+        initial_velocity: 400.0,
+        initial_velocity_randomness: 0.8,
+        size: 16.0,
+        size_randomness: 0.3,
+        atlas: Some(AtlasConfig::new(5, 1, 0..)),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+use crate::formation::{Slot, Spline, COLUMNS, ROWS};
+use crate::world::{EnemyKind, Movement};
+
+// Seconds between the enemies of a spawn entry when it doesn't say, also the pause before a
+// repeating timeline starts over
+const DEFAULT_INTERVAL: f32 = 0.5;
+
+// Spawn Entry Struct - A group of enemies of one kind from a level file
+#[derive(Clone, Debug)]
+pub struct SpawnEntry {
+    pub kind: EnemyKind,
+    pub delay: f32,
+    pub count: u32,
+    pub interval: f32,
+    pub x: Option<f32>,
+    pub path: Option<Movement>,
+    // Flight path flown first, in fractions of the play area
+    pub spline: Option<Spline>,
+    pub slot: Option<Slot>,
+}
+
+// Level Waves Struct - Everything spawned during one level
+#[derive(Clone, Debug, Default)]
+pub struct LevelWaves {
+    pub spawns: Vec<SpawnEntry>,
+    pub repeat: bool,
+}
+
+// Waves Struct - The levels that have a level file
+#[derive(Clone, Default)]
+pub struct Waves {
+    levels: HashMap<u32, LevelWaves>,
+}
+
+impl Waves {
+    // Load function - Reads assets/levels/level-1.toml, level-2.toml and so on up to the first one missing.
+    // Broken ones are reported and skipped so the level falls back to random spawns
+    pub async fn load() -> Waves {
+        let mut waves = Waves::default();
+        for level in 1.. {
+            let path = format!("levels/level-{}.toml", level);
+            let Ok(text) = load_string(&path).await else {
+                break;
+            };
+            match LevelWaves::parse(&text) {
+                Ok(level_waves) => {
+                    waves.levels.insert(level, level_waves);
+                }
+                Err(error) => eprintln!("Could not load {}: {}", path, error),
+            }
+        }
+        waves
+    }
+
+    pub fn level(&self, level: u32) -> Option<&LevelWaves> {
+        self.levels.get(&level)
+    }
+}
+
+// Level File Struct - A level file as it's written, see the README for what each key does. Unknown keys
+// are most likely typos, so they're errors rather than ignored
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    #[serde(default)]
+    repeat: bool,
+    #[serde(default)]
+    path: Vec<PathTable>,
+    #[serde(default)]
+    spawn: Vec<SpawnTable>,
+}
+
+// Path Table Struct - A [[path]], its points across and down the play area from 0.0 to 1.0
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathTable {
+    name: String,
+    points: Vec<[f32; 2]>,
+}
+
+// Spawn Table Struct - A [[spawn]], path is one of the movements or the name of a [[path]]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpawnTable {
+    kind: String,
+    #[serde(default)]
+    delay: f32,
+    #[serde(default = "default_count")]
+    count: u32,
+    #[serde(default = "default_interval")]
+    interval: f32,
+    x: Option<f32>,
+    path: Option<String>,
+    column: Option<u32>,
+    row: Option<u32>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+fn default_interval() -> f32 {
+    DEFAULT_INTERVAL
+}
+
+impl LevelWaves {
+    // Parse function - Reads a level file, then checks the names and formation slots it uses
+    pub fn parse(text: &str) -> Result<LevelWaves, WaveError> {
+        let file: LevelFile = toml::from_str(text)?;
+
+        let mut paths = HashMap::new();
+        for (index, path) in file.path.into_iter().enumerate() {
+            if path.points.len() < 2 {
+                return Err(WaveError::invalid(
+                    "path",
+                    index,
+                    "a path needs at least two points",
+                ));
+            }
+            let points = path.points.iter().map(|&[x, y]| vec2(x, y)).collect();
+            paths.insert(path.name, Spline::new(points));
+        }
+
+        let spawns = file
+            .spawn
+            .into_iter()
+            .enumerate()
+            .map(|(index, spawn)| {
+                spawn
+                    .into_entry(&paths)
+                    .map_err(|reason| WaveError::invalid("spawn", index, &reason))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(LevelWaves {
+            spawns,
+            repeat: file.repeat,
+        })
+    }
+}
+
+impl SpawnTable {
+    // Into entry function - Looks up the kind and path by name, returns why the table can't be used if it
+    // can't
+    fn into_entry(self, paths: &HashMap<String, Spline>) -> Result<SpawnEntry, String> {
+        let kind = EnemyKind::from_id(&self.kind)
+            .ok_or_else(|| format!("unknown kind \"{}\"", self.kind))?;
+
+        let (mut path, mut spline) = (None, None);
+        if let Some(name) = &self.path {
+            path = Movement::from_id(name);
+            spline = paths.get(name).cloned();
+            if path.is_none() && spline.is_none() {
+                return Err(format!("unknown path \"{}\"", name));
+            }
+        }
+
+        let slot = match (self.column, self.row) {
+            (Some(column), Some(row)) => Some(Slot { column, row }),
+            (None, None) => None,
+            _ => return Err("a formation slot needs both a column and a row".to_string()),
+        };
+        if let Some(slot) = slot {
+            if spline.is_none() {
+                return Err("only enemies flying a [[path]] can join the formation".to_string());
+            }
+            if slot.column + self.count > COLUMNS || slot.row >= ROWS {
+                return Err(format!(
+                    "the formation is {} columns by {} rows",
+                    COLUMNS, ROWS
+                ));
+            }
+        }
+
+        Ok(SpawnEntry {
+            kind,
+            delay: self.delay,
+            count: self.count,
+            interval: self.interval,
+            x: self.x.map(|x| x.clamp(0.0, 1.0)),
+            path,
+            spline,
+            slot,
+        })
+    }
+}
+
+// Wave Error Enum - What's wrong with a level file
+#[derive(Debug)]
+pub enum WaveError {
+    // Not TOML, or keys and values a level file doesn't have
+    Toml(toml::de::Error),
+    // A table that reads fine but can't be used, numbered from 1 among the tables of its kind
+    Invalid {
+        table: &'static str,
+        number: usize,
+        reason: String,
+    },
+}
+
+impl WaveError {
+    fn invalid(table: &'static str, index: usize, reason: &str) -> WaveError {
+        WaveError::Invalid {
+            table,
+            number: index + 1,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WaveError::Toml(error) => write!(f, "{}", error),
+            WaveError::Invalid {
+                table,
+                number,
+                reason,
+            } => write!(f, "[[{}]] {}: {}", table, number, reason),
+        }
+    }
+}
+
+impl From<toml::de::Error> for WaveError {
+    fn from(error: toml::de::Error) -> Self {
+        WaveError::Toml(error)
+    }
+}
+
+// Timeline Struct - A level's spawns in the order they're due, played back as the level goes on
+pub struct Timeline {
+    spawns: Vec<(f32, SpawnEntry)>,
+    repeat: bool,
+    next: usize,
+    // Seconds since the timeline (or this time through it) started
+    time: f32,
+}
+
+impl Timeline {
+    pub fn new(level_waves: &LevelWaves) -> Timeline {
+        let mut spawns = vec![];
+        for entry in &level_waves.spawns {
+            for index in 0..entry.count {
+                // Each enemy of a group takes the next slot along
+                let mut entry = entry.clone();
+                if let Some(slot) = entry.slot.as_mut() {
+                    slot.column += index;
+                }
+                spawns.push((entry.delay + index as f32 * entry.interval, entry));
+            }
+        }
+        // Stable, so enemies due at the same moment spawn in file order
+        spawns.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Timeline {
+            spawns,
+            repeat: level_waves.repeat,
+            next: 0,
+            time: 0.0,
+        }
+    }
+
+    // Finished function - True once every spawn is out, a repeating timeline never finishes
+    pub fn finished(&self) -> bool {
+        let repeats = self.repeat && !self.spawns.is_empty();
+        self.next >= self.spawns.len() && !repeats
+    }
+
+    // Update function - Advances by delta_time seconds, returns the spawns that came due
+    pub fn update(&mut self, delta_time: f32) -> Vec<SpawnEntry> {
+        self.time += delta_time;
+
+        let mut due = vec![];
+        while let Some((time, entry)) = self.spawns.get(self.next) {
+            if *time > self.time {
+                break;
+            }
+            due.push(entry.clone());
+            self.next += 1;
+        }
+
+        // Start over once the last spawn is out, timed from when it was due
+        if let Some((last, _)) = self.spawns.last() {
+            if self.repeat && self.next >= self.spawns.len() {
+                self.time -= last + DEFAULT_INTERVAL;
+                self.next = 0;
+            }
+        }
+
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_level_files() {
+        let cases = [
+            include_str!("../assets/levels/level-1.toml"),
+            include_str!("../assets/levels/level-2.toml"),
+            include_str!("../assets/levels/level-3.toml"),
+            "",
+            "# only a comment\n\nrepeat = false\n",
+            "[[spawn]]\nkind = \"diver\" # a comment\ndelay = 2\n",
+            "[[path]]\nname = \"swoop\"\npoints = [\n  [0.0, -0.1], # in\n  [0.5, 0.5],\n]\n\n\
+             [[spawn]]\nkind = \"weaver\"\ncount = 3\npath = \"swoop\"\ncolumn = 5\nrow = 4\n",
+        ];
+        for text in cases {
+            if let Err(error) = LevelWaves::parse(text) {
+                panic!("{}\n{}", error, text);
+            }
+        }
+
+        let level_waves =
+            LevelWaves::parse("repeat = true\n[[spawn]]\nkind = \"chaser\"\nx = 2.0\n").unwrap();
+        assert!(level_waves.repeat);
+        assert_eq!(level_waves.spawns[0].count, 1);
+        assert_eq!(level_waves.spawns[0].interval, DEFAULT_INTERVAL);
+        assert_eq!(level_waves.spawns[0].x, Some(1.0));
+    }
+
+    #[test]
+    fn reports_broken_level_files() {
+        // Anything serde turns down is a TOML error, with the line it's on
+        let syntax = [
+            "[[wave]]",
+            "repeat",
+            "speed = 1",
+            "repeat = \"yes\"",
+            "[[path]]\npoints = [[0, 0], [1, 1]]",
+            "[[path]]\nname = \"p\"\npoints = [[0, 0], [1]]",
+            "[[spawn]]\ndelay = 1",
+            "[[spawn]]\nkind = \"diver\"\nspeed = 1",
+            "[[spawn]]\nkind = \"diver\"\ncount = -1",
+        ];
+        for text in syntax {
+            match LevelWaves::parse(text) {
+                Err(WaveError::Toml(error)) => assert!(error.span().is_some(), "{}", error),
+                _ => panic!("parsed {:?}", text),
+            }
+        }
+
+        let path = "[[path]]\nname = \"p\"\npoints = [[0, 0], [1, 1]]\n";
+        let cases = [
+            (
+                "[[path]]\nname = \"p\"\npoints = [[0, 0]]".to_string(),
+                "[[path]] 1: a path needs at least two points",
+            ),
+            (
+                "[[spawn]]\nkind = \"diver\"\n[[spawn]]\nkind = \"ufo\"".to_string(),
+                "[[spawn]] 2: unknown kind \"ufo\"",
+            ),
+            (
+                "[[spawn]]\nkind = \"diver\"\npath = \"loop\"".to_string(),
+                "[[spawn]] 1: unknown path \"loop\"",
+            ),
+            (
+                "[[spawn]]\nkind = \"diver\"\npath = \"dive\"\ncolumn = 1".to_string(),
+                "[[spawn]] 1: a formation slot needs both a column and a row",
+            ),
+            (
+                "[[spawn]]\nkind = \"diver\"\npath = \"dive\"\ncolumn = 1\nrow = 1".to_string(),
+                "[[spawn]] 1: only enemies flying a [[path]] can join the formation",
+            ),
+            (
+                format!(
+                    "{}[[spawn]]\nkind = \"weaver\"\npath = \"p\"\ncount = 2\ncolumn = {}\nrow = 0",
+                    path,
+                    COLUMNS - 1
+                ),
+                "[[spawn]] 1: the formation is 8 columns by 5 rows",
+            ),
+        ];
+        for (text, expected) in cases {
+            match LevelWaves::parse(&text) {
+                Ok(_) => panic!("parsed {:?}", text),
+                Err(error) => assert_eq!(error.to_string(), expected),
+            }
+        }
+    }
+}
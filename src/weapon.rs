@@ -0,0 +1,218 @@
+use macroquad::prelude::*;
+
+use crate::hitbox::{Hitbox, Hitboxes};
+
+// Weapons go from level 1 up to this, one level per pickup of the weapon already held
+pub const MAX_WEAPON_LEVEL: u32 = 3;
+
+// Weapon Kind Enum - The player's weapons, each firing its own kind of projectile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeaponKind {
+    // Fast, light shots, more of them each level
+    Laser,
+    // Slow, heavy shots that take several hit points at once
+    Bolt,
+    // Beams that wave from side to side, braided together at higher levels
+    WaveBeam,
+    // Slow missiles that turn towards the nearest enemy
+    Homing,
+}
+
+// Weapon Stats Struct - How a weapon fires at one of its levels
+pub struct WeaponStats {
+    // Seconds between volleys
+    pub fire_interval: f32,
+    // Projectiles per volley, fanned out spread radians apart
+    pub projectiles: u32,
+    pub spread: f32,
+    pub speed: f32,
+    // Hit points each projectile takes off what it hits
+    pub damage: u32,
+    pub size: f32,
+}
+
+// Weapon Sprite Struct - Which row of the laser bolt sheet a weapon's projectiles use, how fast it
+// animates and what colour it's tinted
+pub struct WeaponSprite {
+    pub row: u32,
+    pub fps: u32,
+    pub color: Color,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 4] = [
+        WeaponKind::Laser,
+        WeaponKind::Bolt,
+        WeaponKind::WaveBeam,
+        WeaponKind::Homing,
+    ];
+
+    // Name shown in the HUD
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::Laser => "Laser",
+            WeaponKind::Bolt => "Bolt",
+            WeaponKind::WaveBeam => "Wave Beam",
+            WeaponKind::Homing => "Homing Missiles",
+        }
+    }
+
+    // Stats function - level goes from 1 to MAX_WEAPON_LEVEL
+    pub fn stats(&self, level: u32) -> WeaponStats {
+        let index = (level.clamp(1, MAX_WEAPON_LEVEL) - 1) as usize;
+        match self {
+            WeaponKind::Laser => WeaponStats {
+                fire_interval: [0.2, 0.16, 0.12][index],
+                projectiles: [1, 2, 3][index],
+                spread: 0.08,
+                speed: 400.0,
+                damage: 1,
+                size: 32.0,
+            },
+            WeaponKind::Bolt => WeaponStats {
+                fire_interval: 0.35,
+                projectiles: [1, 1, 2][index],
+                spread: 0.15,
+                speed: 300.0,
+                damage: [2, 3, 3][index],
+                size: [32.0, 40.0, 40.0][index],
+            },
+            WeaponKind::WaveBeam => WeaponStats {
+                fire_interval: 0.3,
+                projectiles: [1, 2, 3][index],
+                spread: 0.0,
+                speed: 350.0,
+                damage: 1,
+                size: 36.0,
+            },
+            WeaponKind::Homing => WeaponStats {
+                fire_interval: [0.45, 0.4, 0.35][index],
+                projectiles: [1, 2, 3][index],
+                spread: 0.6,
+                speed: 250.0,
+                damage: 1,
+                size: 24.0,
+            },
+        }
+    }
+
+    pub fn sprite(&self) -> WeaponSprite {
+        match self {
+            WeaponKind::Laser => WeaponSprite {
+                row: 0,
+                fps: 12,
+                color: WHITE,
+            },
+            WeaponKind::Bolt => WeaponSprite {
+                row: 1,
+                fps: 8,
+                color: PINK,
+            },
+            WeaponKind::WaveBeam => WeaponSprite {
+                row: 0,
+                fps: 16,
+                color: VIOLET,
+            },
+            WeaponKind::Homing => WeaponSprite {
+                row: 1,
+                fps: 12,
+                color: LIME,
+            },
+        }
+    }
+
+    // Hitbox function - The hitbox of the sprite row its projectiles are drawn with
+    pub fn hitbox<'a>(&self, hitboxes: &'a Hitboxes) -> &'a Hitbox {
+        match self.sprite().row {
+            0 => &hitboxes.bullet,
+            _ => &hitboxes.bolt,
+        }
+    }
+}
+
+// Weapon Struct - The weapon the player has and its level
+#[derive(Clone, Copy, Debug)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub level: u32,
+}
+
+impl Default for Weapon {
+    // Default function - Every run starts with a level 1 laser
+    fn default() -> Weapon {
+        Weapon {
+            kind: WeaponKind::Laser,
+            level: 1,
+        }
+    }
+}
+
+impl Weapon {
+    pub fn stats(&self) -> WeaponStats {
+        self.kind.stats(self.level)
+    }
+
+    // Upgrade function - Picking up the weapon already held levels it up, any other weapon is swapped in
+    // at the same level
+    pub fn upgrade(&mut self, kind: WeaponKind) {
+        if kind == self.kind {
+            self.level = (self.level + 1).min(MAX_WEAPON_LEVEL);
+        } else {
+            self.kind = kind;
+        }
+    }
+
+    // Downgrade function - A level is lost with each life
+    pub fn downgrade(&mut self) {
+        self.level = (self.level - 1).max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_weapon_levels_up_and_another_is_swapped_in() {
+        let mut weapon = Weapon::default();
+        for level in 2..=MAX_WEAPON_LEVEL + 1 {
+            weapon.upgrade(WeaponKind::Laser);
+            assert_eq!(weapon.level, level.min(MAX_WEAPON_LEVEL));
+        }
+
+        weapon.upgrade(WeaponKind::Homing);
+        assert_eq!(weapon.kind, WeaponKind::Homing);
+        assert_eq!(weapon.level, MAX_WEAPON_LEVEL);
+    }
+
+    #[test]
+    fn levels_are_lost_down_to_the_first() {
+        let mut weapon = Weapon {
+            kind: WeaponKind::Bolt,
+            level: 2,
+        };
+        weapon.downgrade();
+        assert_eq!(weapon.level, 1);
+        weapon.downgrade();
+        assert_eq!(weapon.level, 1);
+        assert_eq!(weapon.kind, WeaponKind::Bolt);
+    }
+
+    #[test]
+    fn higher_levels_are_never_worse() {
+        for kind in WeaponKind::ALL {
+            for level in 1..MAX_WEAPON_LEVEL {
+                let (lower, higher) = (kind.stats(level), kind.stats(level + 1));
+                assert!(higher.fire_interval <= lower.fire_interval);
+                assert!(higher.projectiles >= lower.projectiles);
+                assert!(higher.damage >= lower.damage);
+            }
+            // Levels outside the range use the nearest one
+            assert_eq!(kind.stats(0).projectiles, kind.stats(1).projectiles);
+            assert_eq!(
+                kind.stats(MAX_WEAPON_LEVEL + 1).projectiles,
+                kind.stats(MAX_WEAPON_LEVEL).projectiles
+            );
+        }
+    }
+}
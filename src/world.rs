@@ -0,0 +1,1610 @@
+use std::f32::consts::TAU;
+
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+
+use crate::boss::Boss;
+use crate::events::GameEvent;
+use crate::formation::{Flight, Formation, Slot, Spline};
+use crate::hitbox::{Hitbox, Hitboxes};
+use crate::powerup::{
+    ActivePowerUps, Pickup, PowerUp, PowerUpKind, DROP_CHANCE, SCORE_MULTIPLIER, WEAPON_DROP_CHANCE,
+};
+use crate::spatial::{SpatialHash, GRID_CELL_SIZE};
+use crate::waves::{Timeline, Waves};
+use crate::weapon::{Weapon, WeaponKind};
+
+// Movement speed
+pub const MOVEMENT_SPEED: f32 = 200.0;
+
+// Size every run is played at, whatever the size of the window. Drawing scales it to fit, so resizing
+// the window or going fullscreen doesn't change the game (or replays of it)
+pub const PLAY_WIDTH: f32 = 800.0;
+pub const PLAY_HEIGHT: f32 = 600.0;
+
+// Fixed simulation rate - the world is always stepped by TIME_STEP so gameplay doesn't depend on FPS
+pub const TICK_RATE: f32 = 120.0;
+pub const TIME_STEP: f32 = 1.0 / TICK_RATE;
+
+// Average number of enemies spawned per second
+const ENEMY_SPAWN_RATE: f32 = 3.0;
+
+// Lives at the start of a run, and the score needed for each extra one
+const STARTING_LIVES: u32 = 3;
+const EXTRA_LIFE_SCORE: u32 = 5000;
+
+// Seconds the player is gone after losing a life, then how long they can't be hit once they're back
+const RESPAWN_TIME: f32 = 1.5;
+const INVULNERABLE_TIME: f32 = 2.0;
+
+// Spread shots fire the weapon's volley SPREAD_ANGLE (radians) either side as well, and rapid fire
+// shoots RAPID_FIRE_RATE times as fast
+const SPREAD_ANGLE: f32 = 0.2;
+const RAPID_FIRE_RATE: f32 = 2.0;
+
+// How far wave beams swing either side of their path, and how fast (radians per second)
+const BEAM_WAVE_AMPLITUDE: f32 = 24.0;
+const BEAM_WAVE_FREQUENCY: f32 = 12.0;
+// Homing missiles turn towards their target at up to this many radians per second
+const HOMING_TURN_RATE: f32 = 5.0;
+
+// Seconds an enemy flashes after a hit that doesn't destroy it
+pub const HIT_FLASH_TIME: f32 = 0.1;
+
+// How far weavers swing either side of where they spawned, and how fast (radians per second)
+const WEAVE_AMPLITUDE: f32 = 40.0;
+const WEAVE_FREQUENCY: f32 = 3.0;
+// Divers move sideways at this fraction of their speed
+const DIVE_SLOPE: f32 = 0.75;
+// Chasers steer towards the player at up to this fraction of their speed
+const CHASE_STEER: f32 = 0.6;
+// Strafers stop somewhere in this part of the screen, then strafe for STRAFE_TIME seconds before moving on
+const STRAFE_STOP_MIN: f32 = 0.15;
+const STRAFE_STOP_MAX: f32 = 0.35;
+const STRAFE_TIME: f32 = 4.0;
+
+// Speed enemies fly along paths and back to the formation
+const FLIGHT_SPEED: f32 = 220.0;
+// Average seconds between enemies breaking off from the formation to dive at the player
+const FORMATION_DIVE_INTERVAL: f32 = 2.5;
+
+// Enemy bolts fly at this speed, drawn at this size
+pub const ENEMY_BULLET_SPEED: f32 = 200.0;
+const ENEMY_BULLET_SIZE: f32 = 24.0;
+
+// A boss comes every BOSS_LEVEL_INTERVAL levels, and is worth BOSS_SCORE points on top of its turrets
+const BOSS_LEVEL_INTERVAL: u32 = 5;
+const BOSS_SCORE: u32 = 2000;
+
+// Input Struct - What the player is pressing during a step
+#[derive(Clone, Copy, Default)]
+pub struct Input {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    // Fire is held down, rapid fire keeps shooting while it is
+    pub fire_held: bool,
+    // Analog stick, -127 to 127 on each axis (y down). Stored as whole numbers so replays play back exactly
+    pub stick_x: i8,
+    pub stick_y: i8,
+}
+
+// World Struct - The game simulation, runs without a window
+pub struct World {
+    pub width: f32,
+    pub height: f32,
+    pub player: Shape,
+    pub enemies: Vec<Enemy>,
+    pub bullets: Vec<Bullet>,
+    // Bolts fired by enemies, kept apart from the player's bullets so neither side hits its own shots
+    pub enemy_bullets: Vec<Bullet>,
+    // Pickups drifting down, and the power-ups the player has picked up
+    pub power_ups: Vec<PowerUp>,
+    pub active_power_ups: ActivePowerUps,
+    pub weapon: Weapon,
+    // The boss being fought, random enemies stop spawning until it's defeated
+    pub boss: Option<Boss>,
+    pub score: u32,
+    pub level: u32,
+    pub lives: u32,
+    // Seconds until the player comes back after losing a life (0.0 while they're alive)
+    pub respawn_timer: f32,
+    // Seconds left that the player can't be hit
+    pub invulnerable_timer: f32,
+    // Seconds until the weapon can fire again
+    fire_cooldown: f32,
+    pub game_over: bool,
+    pub seed: u64,
+    pub hitboxes: Hitboxes,
+    // Spawn timelines from the level files, and the one for the current level if it has a file
+    waves: Waves,
+    timeline: Option<Timeline>,
+    // The grid enemies flying in along paths settle into, and the seconds until the next one dives
+    formation: Formation,
+    dive_timer: f32,
+    next_extra_life: u32,
+    next_boss_level: u32,
+    bosses_fought: u32,
+    rng: RandGenerator,
+    grid: SpatialHash,
+    events: Vec<GameEvent>,
+}
+
+impl World {
+    // New function - Player starts in the middle of the play area. The same seed (and level files) always
+    // gives the same enemies
+    pub fn new(width: f32, height: f32, seed: u64, hitboxes: Hitboxes, waves: Waves) -> World {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        let timeline = waves.level(1).map(Timeline::new);
+
+        World {
+            width,
+            height,
+            player: Shape {
+                size: 32.0,
+                speed: MOVEMENT_SPEED,
+                x: width / 2.0,
+                y: height / 2.0,
+                prev_x: width / 2.0,
+                prev_y: height / 2.0,
+                collided: false,
+                hitbox: hitboxes.player.clone(),
+            },
+            enemies: vec![],
+            bullets: vec![],
+            enemy_bullets: vec![],
+            power_ups: vec![],
+            active_power_ups: ActivePowerUps::default(),
+            weapon: Weapon::default(),
+            boss: None,
+            score: 0,
+            level: 1,
+            lives: STARTING_LIVES,
+            respawn_timer: 0.0,
+            invulnerable_timer: 0.0,
+            fire_cooldown: 0.0,
+            game_over: false,
+            seed,
+            hitboxes,
+            waves,
+            timeline,
+            formation: Formation::new(width),
+            dive_timer: FORMATION_DIVE_INTERVAL,
+            next_extra_life: EXTRA_LIFE_SCORE,
+            next_boss_level: BOSS_LEVEL_INTERVAL,
+            bosses_fought: 0,
+            rng,
+            grid: SpatialHash::new(GRID_CELL_SIZE),
+            events: vec![],
+        }
+    }
+
+    // Step function - Advances the simulation by delta_time seconds, returns what happened during the step
+    pub fn step(&mut self, delta_time: f32, input: Input) -> &[GameEvent] {
+        self.events.clear();
+
+        // Remember where everything was so drawing can interpolate between steps
+        self.player.store_position();
+        for enemy in &mut self.enemies {
+            enemy.shape.store_position();
+            enemy.hit_flash = (enemy.hit_flash - delta_time).max(0.0);
+        }
+        for bullet in self.bullets.iter_mut().chain(&mut self.enemy_bullets) {
+            bullet.shape.store_position();
+        }
+        for power_up in &mut self.power_ups {
+            power_up.shape.store_position();
+        }
+        if let Some(boss) = self.boss.as_mut() {
+            boss.store_position();
+        }
+
+        // Count down to the player coming back, their invulnerability after that and their power-ups
+        self.invulnerable_timer = (self.invulnerable_timer - delta_time).max(0.0);
+        self.fire_cooldown = (self.fire_cooldown - delta_time).max(0.0);
+        self.active_power_ups.update(delta_time);
+        if self.respawn_timer > 0.0 {
+            self.respawn_timer -= delta_time;
+            if self.respawn_timer <= 0.0 {
+                self.respawn_player();
+            }
+        }
+
+        // Handle input, keys move at full speed and the stick anywhere up to it
+        let mut direction = vec2(input.stick_x as f32 / 127.0, input.stick_y as f32 / 127.0);
+        if input.up {
+            direction.y -= 1.0;
+        }
+        if input.left {
+            direction.x -= 1.0;
+        }
+        if input.down {
+            direction.y += 1.0;
+        }
+        if input.right {
+            direction.x += 1.0;
+        }
+        direction = direction.clamp(vec2(-1.0, -1.0), vec2(1.0, 1.0));
+        if self.player_alive() {
+            self.player.x += direction.x * MOVEMENT_SPEED * delta_time;
+            self.player.y += direction.y * MOVEMENT_SPEED * delta_time;
+        }
+        // Fire as fast as the weapon allows, rapid fire speeds that up and keeps shooting while fire is held
+        let rapid_fire = self.active_power_ups.active(PowerUpKind::RapidFire);
+        let firing = input.fire || (rapid_fire && input.fire_held);
+        if firing && self.fire_cooldown <= 0.0 && self.player_alive() {
+            self.fire();
+            self.fire_cooldown = self.weapon.stats().fire_interval;
+            if rapid_fire {
+                self.fire_cooldown /= RAPID_FIRE_RATE;
+            }
+        }
+
+        // Clamp X and Y so player stays within the play area
+        self.player.x = clamp(self.player.x, 0.0, self.width);
+        self.player.y = clamp(self.player.y, 0.0, self.height);
+
+        // Bring in a boss when a boss level is reached
+        if self.boss.is_none() && self.level >= self.next_boss_level {
+            self.bosses_fought += 1;
+            self.next_boss_level = (self.level / BOSS_LEVEL_INTERVAL + 1) * BOSS_LEVEL_INTERVAL;
+            self.boss = Some(Boss::new(
+                self.bosses_fought,
+                self.width,
+                self.height,
+                &self.hitboxes,
+            ));
+            self.events.push(GameEvent::BossIncoming);
+        }
+
+        // Enemies stop coming during boss fights
+        if self.boss.is_none() {
+            self.spawn_enemies(delta_time);
+        }
+
+        // Enemy and bullet movement, chasers only follow the player while they're there
+        let target = self
+            .player_alive()
+            .then(|| vec2(self.player.x, self.player.y));
+        self.formation.update(delta_time);
+        self.dive_timer -= delta_time;
+        if self.dive_timer <= 0.0 {
+            self.dive_timer = FORMATION_DIVE_INTERVAL * self.rng.gen_range(0.5, 1.5);
+            if let Some(target) = target {
+                self.formation_dive(target);
+            }
+        }
+        for enemy in &mut self.enemies {
+            enemy.update(delta_time, target, self.width, self.height, &self.formation);
+        }
+        let homing_targets = self.homing_targets();
+        for bullet in self.bullets.iter_mut().chain(&mut self.enemy_bullets) {
+            bullet.update(delta_time, &homing_targets);
+        }
+        for power_up in &mut self.power_ups {
+            power_up.update(delta_time);
+        }
+
+        // Enemies and the boss shoot at the player while they're there
+        for index in 0..self.enemies.len() {
+            if let Some(velocity) = self.enemies[index].fire(delta_time, target) {
+                let shape = &self.enemies[index].shape;
+                self.fire_bolt(vec2(shape.x, shape.y + shape.size / 2.0), velocity);
+            }
+        }
+        if let Some(boss) = self.boss.as_mut() {
+            for (position, velocity) in boss.update(delta_time, target, self.width) {
+                self.fire_bolt(position, velocity);
+            }
+        }
+
+        // Retain only entities inside the play area, discard others. Enemies flying a path are kept, a dive
+        // from the formation ends below the play area and comes back in from the top
+        let height = self.height;
+        self.enemies
+            .retain(|enemy| enemy.flight.is_some() || enemy.shape.y < height + enemy.shape.size);
+        let play_area = Rect::new(0.0, 0.0, self.width, self.height);
+        self.bullets
+            .retain(|bullet| play_area.overlaps(&bullet.shape.rect()));
+        self.enemy_bullets
+            .retain(|bullet| play_area.overlaps(&bullet.shape.rect()));
+        self.power_ups
+            .retain(|power_up| power_up.shape.y < height + power_up.shape.size);
+
+        // Retain only entities that haven't collided, discard others
+        self.enemies.retain(|enemy| !enemy.shape.collided);
+        self.bullets.retain(|bullet| !bullet.shape.collided);
+        self.enemy_bullets.retain(|bullet| !bullet.shape.collided);
+
+        // Sort enemies into the grid so collision checks only look at nearby enemies
+        self.grid.clear();
+        for (index, enemy) in self.enemies.iter().enumerate() {
+            self.grid.insert(index, enemy.shape.rect());
+        }
+        let mut nearby = vec![];
+
+        // Check for bullet collisions, each bullet damages the first enemy it hits that is still there
+        for bullet in self.bullets.iter_mut() {
+            self.grid.query(bullet.shape.rect(), &mut nearby);
+            for &index in &nearby {
+                let enemy = &mut self.enemies[index];
+                if enemy.shape.collided || !bullet.shape.collides_with(&enemy.shape) {
+                    continue;
+                }
+                bullet.shape.collided = true;
+                enemy.hp = enemy.hp.saturating_sub(bullet.damage);
+                let pos = vec2(enemy.shape.x, enemy.shape.y);
+                if enemy.hp == 0 {
+                    enemy.shape.collided = true;
+                    self.events.push(GameEvent::EnemyDestroyed {
+                        size: enemy.shape.size,
+                        pos,
+                    });
+                } else {
+                    enemy.hit_flash = HIT_FLASH_TIME;
+                    self.events.push(GameEvent::EnemyHit { pos });
+                }
+                break;
+            }
+        }
+
+        // Check for bullet collisions with the boss
+        if let Some(boss) = self.boss.as_mut() {
+            for bullet in self
+                .bullets
+                .iter_mut()
+                .filter(|bullet| !bullet.shape.collided)
+            {
+                // Turrets first, so a bullet touching them and the shielded core isn't wasted on the core.
+                // Parts already destroyed this step are skipped, they're removed once every bullet is checked
+                let mut parts = (1..boss.parts.len()).chain([0]);
+                if let Some(index) = parts.find(|&index| {
+                    let part = &boss.parts[index];
+                    part.hp > 0 && bullet.shape.collides_with(&part.shape)
+                }) {
+                    bullet.shape.collided = true;
+                    if boss.shielded(index) {
+                        self.events.push(GameEvent::ShotDeflected {
+                            pos: vec2(bullet.shape.x, bullet.shape.y),
+                        });
+                    } else {
+                        boss.hit(index, bullet.damage, &mut self.events);
+                    }
+                }
+            }
+            if boss.defeated() {
+                self.boss = None;
+            } else {
+                boss.remove_destroyed();
+            }
+        }
+
+        // Destroyed enemies sometimes drop a power-up
+        self.drop_power_ups();
+
+        // Pick up power-ups the player touches
+        if self.player_alive() {
+            for power_up in &mut self.power_ups {
+                if !self.player.collides_with(&power_up.shape) {
+                    continue;
+                }
+                power_up.shape.collided = true;
+                let pos = vec2(power_up.shape.x, power_up.shape.y);
+                match power_up.pickup {
+                    Pickup::PowerUp(kind) => {
+                        self.active_power_ups.activate(kind);
+                        self.events.push(GameEvent::PowerUpCollected { kind, pos });
+                    }
+                    Pickup::Weapon(kind) => {
+                        self.weapon.upgrade(kind);
+                        self.events.push(GameEvent::WeaponCollected {
+                            kind,
+                            level: self.weapon.level,
+                            pos,
+                        });
+                    }
+                }
+            }
+            self.power_ups.retain(|power_up| !power_up.shape.collided);
+        }
+
+        // Check for player collisions with enemies and their bolts, while they're there and not invulnerable.
+        // The shield soaks up the hit, bolts that hit it are still used up
+        if self.player_alive() && self.invulnerable_timer <= 0.0 {
+            self.grid.query(self.player.rect(), &mut nearby);
+            let mut hit = nearby
+                .iter()
+                .any(|&index| self.player.collides_with(&self.enemies[index].shape));
+            if let Some(boss) = &self.boss {
+                hit |= boss
+                    .parts
+                    .iter()
+                    .any(|part| self.player.collides_with(&part.shape));
+            }
+            for bullet in &mut self.enemy_bullets {
+                if self.player.collides_with(&bullet.shape) {
+                    bullet.shape.collided = true;
+                    hit = true;
+                }
+            }
+            if hit && !self.active_power_ups.active(PowerUpKind::Shield) {
+                self.lose_life();
+            }
+        }
+
+        self.update_score();
+
+        &self.events
+    }
+
+    // Fire function - Shoots a volley of the weapon's projectiles from the nose of the player's ship, fanned
+    // out around straight up. Spread shot fires the volley three times over
+    fn fire(&mut self) {
+        let position = vec2(self.player.x, self.player.y - 40.0);
+        let weapon = self.weapon.kind;
+        let stats = self.weapon.stats();
+        let fired = self.bullets.len();
+        let volleys: &[f32] = if self.active_power_ups.active(PowerUpKind::SpreadShot) {
+            &[-SPREAD_ANGLE, 0.0, SPREAD_ANGLE]
+        } else {
+            &[0.0]
+        };
+        for &volley in volleys {
+            for index in 0..stats.projectiles {
+                let offset = index as f32 - (stats.projectiles - 1) as f32 / 2.0;
+                let angle = volley + offset * stats.spread;
+                self.bullets.push(Bullet {
+                    shape: Shape {
+                        size: stats.size,
+                        speed: stats.speed,
+                        x: position.x,
+                        y: position.y,
+                        prev_x: position.x,
+                        prev_y: position.y,
+                        collided: false,
+                        hitbox: weapon.hitbox(&self.hitboxes).clone(),
+                    },
+                    velocity: Vec2::from_angle(angle).rotate(vec2(0.0, -stats.speed)),
+                    damage: stats.damage,
+                    weapon: Some(weapon),
+                    age: 0.0,
+                    // Wave beams fired together are spread out along the wave so they braid
+                    phase: index as f32 / stats.projectiles as f32 * TAU,
+                });
+            }
+        }
+        self.events.push(GameEvent::ShotFired {
+            pos: position,
+            bullets: (self.bullets.len() - fired) as u32,
+        });
+    }
+
+    // Homing targets function - Where every enemy on screen and boss part is, empty when there are no
+    // homing missiles to steer
+    fn homing_targets(&self) -> Vec<Vec2> {
+        let homing = |bullet: &Bullet| bullet.weapon == Some(WeaponKind::Homing);
+        if !self.bullets.iter().any(homing) {
+            return vec![];
+        }
+
+        let mut targets: Vec<Vec2> = self
+            .enemies
+            .iter()
+            .filter(|enemy| enemy.shape.y > 0.0)
+            .map(|enemy| vec2(enemy.shape.x, enemy.shape.y))
+            .collect();
+        if let Some(boss) = &self.boss {
+            targets.extend(
+                boss.parts
+                    .iter()
+                    .map(|part| vec2(part.shape.x, part.shape.y)),
+            );
+        }
+        targets
+    }
+
+    // Fire bolt function - Adds an enemy bolt, from an enemy or the boss
+    fn fire_bolt(&mut self, position: Vec2, velocity: Vec2) {
+        self.enemy_bullets.push(Bullet {
+            shape: Shape {
+                size: ENEMY_BULLET_SIZE,
+                speed: ENEMY_BULLET_SPEED,
+                x: position.x,
+                y: position.y,
+                prev_x: position.x,
+                prev_y: position.y,
+                collided: false,
+                hitbox: self.hitboxes.bolt.clone(),
+            },
+            velocity,
+            damage: 1,
+            weapon: None,
+            age: 0.0,
+            phase: 0.0,
+        });
+    }
+
+    // Spawn enemies function - Spawns from the level file while its timeline has spawns left, then from the
+    // random spawner (chance scales with delta_time so density doesn't depend on tick rate)
+    fn spawn_enemies(&mut self, delta_time: f32) {
+        if let Some(timeline) = self
+            .timeline
+            .as_mut()
+            .filter(|timeline| !timeline.finished())
+        {
+            for entry in timeline.update(delta_time) {
+                let movement = entry.path.unwrap_or(entry.kind.movement());
+                let size = vec2(self.width, self.height);
+                let enemy = self.spawn_enemy(entry.kind, movement, entry.x);
+                if let Some(spline) = &entry.spline {
+                    enemy.fly(spline.scaled(size));
+                    enemy.slot = entry.slot;
+                }
+            }
+        } else if self.rng.gen_range(0.0, 1.0) < ENEMY_SPAWN_RATE * delta_time {
+            let kind = EnemyKind::ALL[self.rng.gen_range(0, EnemyKind::ALL.len())];
+            self.spawn_enemy(kind, kind.movement(), None);
+        }
+    }
+
+    // Spawn enemy function - Adds an enemy of the given kind just above the play area, sized within its
+    // size class and faster on higher levels. x is across the play area from 0.0 to 1.0, None for anywhere
+    fn spawn_enemy(&mut self, kind: EnemyKind, movement: Movement, x: Option<f32>) -> &mut Enemy {
+        let size_class = kind.size_class();
+        let (min_size, max_size) = size_class.size_range();
+        let size = self.rng.gen_range(min_size, max_size);
+
+        let speed_modifier = self.level as f32 / 2.0;
+        let speed = self
+            .rng
+            .gen_range(50.0 * speed_modifier, 150.0 * speed_modifier);
+
+        // Weavers spawn far enough from the edges that their swing stays on screen
+        let margin = match movement {
+            Movement::Weave => size / 2.0 + WEAVE_AMPLITUDE,
+            _ => size / 2.0,
+        };
+        let x = match x {
+            Some(x) => (x * self.width).clamp(margin, self.width - margin),
+            None => self.rng.gen_range(margin, self.width - margin),
+        };
+
+        let direction = if self.rng.gen_range(0.0, 1.0) < 0.5 {
+            -1.0
+        } else {
+            1.0
+        };
+        let stop_y = self.height * self.rng.gen_range(STRAFE_STOP_MIN, STRAFE_STOP_MAX);
+
+        // The first shot comes somewhere in the first interval, so enemies don't all fire together
+        let fire_timer = match kind.weapon() {
+            Some((_, interval)) => interval * self.rng.gen_range(0.5, 1.0),
+            None => 0.0,
+        };
+
+        let hitbox = match size_class {
+            SizeClass::Small => &self.hitboxes.enemy_small,
+            SizeClass::Medium => &self.hitboxes.enemy_medium,
+            SizeClass::Large => &self.hitboxes.enemy_large,
+        };
+
+        self.enemies.push(Enemy {
+            shape: Shape {
+                size,
+                speed,
+                x,
+                y: -size,
+                prev_x: x,
+                prev_y: -size,
+                collided: false,
+                hitbox: hitbox.clone(),
+            },
+            kind,
+            movement,
+            hp: size_class.hit_points(),
+            hit_flash: 0.0,
+            age: 0.0,
+            origin_x: x,
+            direction,
+            stop_y,
+            strafe_timer: STRAFE_TIME,
+            fire_timer,
+            flight: None,
+            slot: None,
+        });
+        self.enemies.last_mut().unwrap()
+    }
+
+    // Formation dive function - Sends a random enemy from the formation diving down through the target
+    fn formation_dive(&mut self, target: Vec2) {
+        let holding: Vec<usize> = (0..self.enemies.len())
+            .filter(|&index| matches!(self.enemies[index].flight, Some(Flight::Holding)))
+            .collect();
+        if holding.is_empty() {
+            return;
+        }
+
+        let enemy = &mut self.enemies[holding[self.rng.gen_range(0, holding.len())]];
+        let from = vec2(enemy.shape.x, enemy.shape.y);
+        enemy.fly(Spline::dive(from, target, self.width, self.height));
+    }
+
+    // Drop power ups function - Rolls for a pickup from every enemy destroyed this step, bosses always drop one
+    fn drop_power_ups(&mut self) {
+        for index in 0..self.events.len() {
+            let pos = match self.events[index] {
+                GameEvent::EnemyDestroyed { pos, .. } => {
+                    if self.rng.gen_range(0.0, 1.0) >= DROP_CHANCE {
+                        continue;
+                    }
+                    pos
+                }
+                GameEvent::BossDefeated { pos } => pos,
+                _ => continue,
+            };
+            let pickup = if self.rng.gen_range(0.0, 1.0) < WEAPON_DROP_CHANCE {
+                Pickup::Weapon(WeaponKind::ALL[self.rng.gen_range(0, WeaponKind::ALL.len())])
+            } else {
+                Pickup::PowerUp(PowerUpKind::ALL[self.rng.gen_range(0, PowerUpKind::ALL.len())])
+            };
+            self.power_ups.push(PowerUp::new(pickup, pos));
+        }
+    }
+
+    // Player alive function - False while waiting to respawn after losing a life
+    pub fn player_alive(&self) -> bool {
+        self.respawn_timer <= 0.0
+    }
+
+    // Lose life function - Ends the run on the last life, otherwise the player respawns after a moment.
+    // Power-ups and a weapon level are lost with the life
+    fn lose_life(&mut self) {
+        self.active_power_ups.clear();
+        self.weapon.downgrade();
+        self.events.push(GameEvent::PlayerHit {
+            pos: vec2(self.player.x, self.player.y),
+        });
+        self.lives -= 1;
+        if self.lives == 0 {
+            self.game_over = true;
+        } else {
+            self.respawn_timer = RESPAWN_TIME;
+        }
+    }
+
+    // Respawn player function - Brings the player back near the bottom of the play area, briefly invulnerable
+    fn respawn_player(&mut self) {
+        self.respawn_timer = 0.0;
+        self.invulnerable_timer = INVULNERABLE_TIME;
+        self.player.x = self.width / 2.0;
+        self.player.y = self.height * 0.8;
+        self.player.store_position();
+        self.events.push(GameEvent::PlayerRespawned {
+            pos: vec2(self.player.x, self.player.y),
+        });
+    }
+
+    // Update score function - Scores this step's destroyed enemies and bosses, levels up every 1000 points
+    // and gives an extra life every EXTRA_LIFE_SCORE points
+    fn update_score(&mut self) {
+        let multiplier = if self.active_power_ups.active(PowerUpKind::ScoreMultiplier) {
+            SCORE_MULTIPLIER
+        } else {
+            1
+        };
+        for index in 0..self.events.len() {
+            self.score += multiplier
+                * match self.events[index] {
+                    GameEvent::EnemyDestroyed { size, .. } => size.round() as u32,
+                    GameEvent::BossDefeated { .. } => BOSS_SCORE,
+                    _ => continue,
+                };
+
+            // Increase level every 1000 points (so enemy speed increases)
+            let new_level = self.score / 1000 + 1;
+            if new_level > self.level {
+                self.level = new_level;
+                self.timeline = self.waves.level(new_level).map(Timeline::new);
+                self.events.push(GameEvent::LevelUp { level: new_level });
+            }
+
+            while self.score >= self.next_extra_life {
+                self.lives += 1;
+                self.next_extra_life += EXTRA_LIFE_SCORE;
+                self.events.push(GameEvent::ExtraLife { lives: self.lives });
+            }
+        }
+    }
+}
+
+// Size Class Enum - Enemies come in three sizes, each with its own sprite, hitbox and hit points
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SizeClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl SizeClass {
+    // Size range function - Smallest and largest size an enemy of this class spawns at
+    pub fn size_range(&self) -> (f32, f32) {
+        match self {
+            SizeClass::Small => (16.0, 32.0),
+            SizeClass::Medium => (32.0, 48.0),
+            SizeClass::Large => (48.0, 64.0),
+        }
+    }
+
+    // Hit points function - How many bullets it takes to destroy an enemy of this size
+    pub fn hit_points(&self) -> u32 {
+        match self {
+            SizeClass::Small => 1,
+            SizeClass::Medium => 2,
+            SizeClass::Large => 4,
+        }
+    }
+}
+
+// Enemy Kind Enum - A type of enemy, with its own sprite, hit points, weapon and way of moving
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnemyKind {
+    Weaver,
+    Diver,
+    Chaser,
+    Strafer,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [
+        EnemyKind::Weaver,
+        EnemyKind::Diver,
+        EnemyKind::Chaser,
+        EnemyKind::Strafer,
+    ];
+
+    // Name used in level files
+    pub fn id(&self) -> &'static str {
+        match self {
+            EnemyKind::Weaver => "weaver",
+            EnemyKind::Diver => "diver",
+            EnemyKind::Chaser => "chaser",
+            EnemyKind::Strafer => "strafer",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<EnemyKind> {
+        EnemyKind::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+
+    // Movement function - How this kind moves unless a level file gives it another path
+    pub fn movement(&self) -> Movement {
+        match self {
+            EnemyKind::Weaver => Movement::Weave,
+            EnemyKind::Diver => Movement::Dive,
+            EnemyKind::Chaser => Movement::Chase,
+            EnemyKind::Strafer => Movement::Strafe,
+        }
+    }
+
+    // Weapon function - How this kind shoots and the seconds between shots, None for kinds that don't
+    pub fn weapon(&self) -> Option<(FirePattern, f32)> {
+        match self {
+            EnemyKind::Weaver => None,
+            EnemyKind::Diver => Some((FirePattern::Straight, 1.5)),
+            EnemyKind::Chaser => Some((FirePattern::Aimed, 2.0)),
+            EnemyKind::Strafer => Some((FirePattern::Aimed, 0.8)),
+        }
+    }
+
+    pub fn size_class(&self) -> SizeClass {
+        match self {
+            EnemyKind::Weaver | EnemyKind::Diver => SizeClass::Small,
+            EnemyKind::Chaser => SizeClass::Medium,
+            EnemyKind::Strafer => SizeClass::Large,
+        }
+    }
+}
+
+// Movement Enum - The path an enemy takes down the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Movement {
+    // Falls straight down
+    Straight,
+    // Weaves from side to side on the way down
+    Weave,
+    // Dives diagonally, bouncing off the sides
+    Dive,
+    // Steers towards the player
+    Chase,
+    // Stops near the top to strafe from side to side, then moves on
+    Strafe,
+}
+
+impl Movement {
+    pub const ALL: [Movement; 5] = [
+        Movement::Straight,
+        Movement::Weave,
+        Movement::Dive,
+        Movement::Chase,
+        Movement::Strafe,
+    ];
+
+    // Name used in level files
+    pub fn id(&self) -> &'static str {
+        match self {
+            Movement::Straight => "straight",
+            Movement::Weave => "weave",
+            Movement::Dive => "dive",
+            Movement::Chase => "chase",
+            Movement::Strafe => "strafe",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Movement> {
+        Movement::ALL
+            .into_iter()
+            .find(|movement| movement.id() == id)
+    }
+}
+
+// Fire Pattern Enum - Which way an enemy's bolts go
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FirePattern {
+    // Straight down
+    Straight,
+    // At where the player is when it fires
+    Aimed,
+}
+
+// Enemy Struct - An enemy ship, destroyed once its hit points run out
+pub struct Enemy {
+    pub shape: Shape,
+    pub kind: EnemyKind,
+    pub movement: Movement,
+    pub hp: u32,
+    // Seconds left of the flash after a hit that didn't destroy it
+    pub hit_flash: f32,
+    // Seconds since it spawned
+    age: f32,
+    // Where a weaver swings around
+    origin_x: f32,
+    // Sideways direction for divers and strafers, -1.0 or 1.0
+    direction: f32,
+    // Where a strafer stops, and how long it has left to strafe there
+    stop_y: f32,
+    strafe_timer: f32,
+    // Seconds until it next fires
+    fire_timer: f32,
+    // Flying a path or in the formation, None while it moves the usual way
+    pub flight: Option<Flight>,
+    // Where it goes in the formation after its path, None for enemies that carry on the usual way
+    pub slot: Option<Slot>,
+}
+
+impl Enemy {
+    // Fly function - Starts flying along a path, it's already in pixels
+    fn fly(&mut self, spline: Spline) {
+        let start = spline.start();
+        if self.flight.is_none() {
+            self.shape.x = start.x;
+            self.shape.y = start.y;
+            self.shape.store_position();
+        }
+        self.flight = Some(Flight::Path { spline, t: 0.0 });
+    }
+
+    // Update function - Moves the enemy along its path or its way of moving. Target is the player, None
+    // while they're respawning
+    fn update(
+        &mut self,
+        delta_time: f32,
+        target: Option<Vec2>,
+        width: f32,
+        height: f32,
+        formation: &Formation,
+    ) {
+        self.age += delta_time;
+        if self.flight.is_some() {
+            self.update_flight(delta_time, height, formation);
+            return;
+        }
+
+        let shape = &mut self.shape;
+        let half_size = shape.size / 2.0;
+
+        match self.movement {
+            Movement::Straight => shape.y += shape.speed * delta_time,
+            Movement::Weave => {
+                shape.y += shape.speed * delta_time;
+                shape.x = self.origin_x + (self.age * WEAVE_FREQUENCY).sin() * WEAVE_AMPLITUDE;
+            }
+            Movement::Dive => {
+                shape.y += shape.speed * delta_time;
+                shape.x += self.direction * shape.speed * DIVE_SLOPE * delta_time;
+            }
+            Movement::Chase => {
+                shape.y += shape.speed * delta_time;
+                if let Some(target) = target {
+                    let steer = shape.speed * CHASE_STEER * delta_time;
+                    shape.x += (target.x - shape.x).clamp(-steer, steer);
+                }
+            }
+            Movement::Strafe => {
+                if shape.y < self.stop_y || self.strafe_timer <= 0.0 {
+                    shape.y += shape.speed * delta_time;
+                } else {
+                    self.strafe_timer -= delta_time;
+                    shape.x += self.direction * shape.speed * delta_time;
+                }
+            }
+        }
+
+        // Divers and strafers bounce off the sides of the play area
+        if shape.x < half_size || shape.x > width - half_size {
+            self.direction = if shape.x < half_size { 1.0 } else { -1.0 };
+            shape.x = shape.x.clamp(half_size, width - half_size);
+        }
+    }
+
+    // Update flight function - Follows the path, then flies to and holds its slot in the formation. Without a
+    // slot it goes back to its usual way of moving from the end of the path
+    fn update_flight(&mut self, delta_time: f32, height: f32, formation: &Formation) {
+        let step = FLIGHT_SPEED * delta_time;
+        let shape = &mut self.shape;
+
+        self.flight = match self.flight.take() {
+            Some(Flight::Path { spline, t }) => {
+                let t = spline.advance(t, step);
+                let position = spline.point(t);
+                shape.x = position.x;
+                shape.y = position.y;
+
+                match (spline.finished(t), self.slot) {
+                    (false, _) => Some(Flight::Path { spline, t }),
+                    // A dive ends off the bottom, it comes back in from the top
+                    (true, Some(slot)) => {
+                        if shape.y >= height {
+                            shape.x = formation.slot_position(slot).x;
+                            shape.y = -shape.size;
+                            shape.store_position();
+                        }
+                        Some(Flight::Joining)
+                    }
+                    (true, None) => {
+                        self.origin_x = shape.x;
+                        self.age = 0.0;
+                        None
+                    }
+                }
+            }
+            Some(Flight::Joining) => {
+                let slot = formation.slot_position(self.slot.expect("joining enemies have a slot"));
+                let position = vec2(shape.x, shape.y).move_towards(slot, step);
+                shape.x = position.x;
+                shape.y = position.y;
+                if position == slot {
+                    Some(Flight::Holding)
+                } else {
+                    Some(Flight::Joining)
+                }
+            }
+            Some(Flight::Holding) => {
+                let slot = formation.slot_position(self.slot.expect("holding enemies have a slot"));
+                shape.x = slot.x;
+                shape.y = slot.y;
+                Some(Flight::Holding)
+            }
+            None => None,
+        };
+    }
+
+    // Fire function - Counts down to the next shot, returns the bolt's velocity when it fires.
+    // Enemies only fire once they're on screen and while there's a player to shoot at
+    fn fire(&mut self, delta_time: f32, target: Option<Vec2>) -> Option<Vec2> {
+        let (pattern, interval) = self.kind.weapon()?;
+        let target = target?;
+        // Enemies in the formation hold their fire until they dive
+        let in_formation = matches!(self.flight, Some(Flight::Joining | Flight::Holding));
+        if self.shape.y < 0.0 || in_formation {
+            return None;
+        }
+
+        self.fire_timer -= delta_time;
+        if self.fire_timer > 0.0 {
+            return None;
+        }
+        self.fire_timer += interval;
+
+        let direction = match pattern {
+            FirePattern::Straight => vec2(0.0, 1.0),
+            FirePattern::Aimed => (target - vec2(self.shape.x, self.shape.y))
+                .try_normalize()
+                .unwrap_or(vec2(0.0, 1.0)),
+        };
+        Some(direction * ENEMY_BULLET_SPEED)
+    }
+}
+
+// Bullet Struct - A projectile fired by the player's weapon or a bolt fired by an enemy
+pub struct Bullet {
+    pub shape: Shape,
+    pub velocity: Vec2,
+    // Hit points it takes off what it hits
+    pub damage: u32,
+    // The weapon that fired it, None for enemy bolts
+    pub weapon: Option<WeaponKind>,
+    // Seconds since it was fired, and where a wave beam starts along its wave (radians)
+    age: f32,
+    phase: f32,
+}
+
+impl Bullet {
+    // Update function - Flies in a straight line, except for wave beams that swing from side to side and
+    // homing missiles that turn towards the nearest of the targets
+    fn update(&mut self, delta_time: f32, targets: &[Vec2]) {
+        self.age += delta_time;
+        let position = vec2(self.shape.x, self.shape.y);
+
+        if self.weapon == Some(WeaponKind::Homing) {
+            let nearest = targets.iter().min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+            if let Some(&target) = nearest {
+                let turn = self.velocity.angle_between(target - position);
+                let max_turn = HOMING_TURN_RATE * delta_time;
+                self.velocity =
+                    Vec2::from_angle(turn.clamp(-max_turn, max_turn)).rotate(self.velocity);
+            }
+        }
+
+        self.shape.x += self.velocity.x * delta_time;
+        self.shape.y += self.velocity.y * delta_time;
+
+        if self.weapon == Some(WeaponKind::WaveBeam) {
+            let wave = (self.age * BEAM_WAVE_FREQUENCY + self.phase).cos();
+            self.shape.x += wave * BEAM_WAVE_AMPLITUDE * BEAM_WAVE_FREQUENCY * delta_time;
+        }
+    }
+}
+
+// Shape Struct
+pub struct Shape {
+    pub size: f32,
+    pub speed: f32,
+    pub x: f32,
+    pub y: f32,
+    pub prev_x: f32,
+    pub prev_y: f32,
+    pub collided: bool,
+    pub hitbox: Hitbox,
+}
+
+impl Shape {
+    pub fn collides_with(&self, other: &Self) -> bool {
+        self.hitbox.overlaps(
+            vec2(self.x, self.y),
+            self.size,
+            &other.hitbox,
+            vec2(other.x, other.y),
+            other.size,
+        )
+    }
+
+    // Interpolated position between the previous and current step (alpha 0.0 to 1.0), used for drawing
+    pub fn lerp_position(&self, alpha: f32) -> Vec2 {
+        vec2(self.prev_x, self.prev_y).lerp(vec2(self.x, self.y), alpha)
+    }
+
+    pub fn store_position(&mut self) {
+        self.prev_x = self.x;
+        self.prev_y = self.y;
+    }
+
+    // Rect function - Bounds of the hitbox
+    pub fn rect(&self) -> Rect {
+        self.hitbox.bounds(vec2(self.x, self.y), self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boss::BossPhase;
+
+    fn new_world(seed: u64) -> World {
+        World::new(800.0, 600.0, seed, Hitboxes::default(), Waves::default())
+    }
+
+    // Run function - Steps the world for seconds with the same input every step
+    fn run(world: &mut World, seconds: f32, input: Input) {
+        for _ in 0..(seconds * TICK_RATE) as usize {
+            world.step(TIME_STEP, input);
+        }
+    }
+
+    #[test]
+    fn player_stays_in_the_play_area() {
+        let mut world = new_world(1);
+        world.invulnerable_timer = 100.0;
+        let up_left = Input {
+            up: true,
+            left: true,
+            ..Default::default()
+        };
+        run(&mut world, 5.0, up_left);
+        assert_eq!((world.player.x, world.player.y), (0.0, 0.0));
+
+        let down_right = Input {
+            down: true,
+            right: true,
+            ..Default::default()
+        };
+        run(&mut world, 5.0, down_right);
+        assert_eq!(
+            (world.player.x, world.player.y),
+            (world.width, world.height)
+        );
+    }
+
+    #[test]
+    fn fire_waits_for_the_weapon() {
+        let mut world = new_world(1);
+        let fire = Input {
+            fire: true,
+            ..Default::default()
+        };
+        world.step(TIME_STEP, fire);
+        assert_eq!(world.bullets.len(), 1);
+        assert!(world.bullets[0].velocity.y < 0.0);
+        assert!(world.bullets[0].shape.y < world.player.y);
+
+        // Holding fire without rapid fire doesn't shoot again until the weapon is ready
+        world.step(TIME_STEP, fire);
+        assert_eq!(world.bullets.len(), 1);
+        let fire_interval = world.weapon.stats().fire_interval;
+        run(&mut world, fire_interval, Input::default());
+        world.step(TIME_STEP, fire);
+        assert_eq!(world.bullets.len(), 2);
+    }
+
+    #[test]
+    fn drawing_interpolates_between_steps() {
+        let mut world = new_world(1);
+        let right = Input {
+            right: true,
+            ..Default::default()
+        };
+        world.step(TIME_STEP, right);
+
+        let before = world.player.lerp_position(0.0);
+        let after = world.player.lerp_position(1.0);
+        assert!((after.x - before.x - MOVEMENT_SPEED * TIME_STEP).abs() < 0.001);
+        assert!(
+            world
+                .player
+                .lerp_position(0.5)
+                .distance((before + after) / 2.0)
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn same_seed_plays_the_same() {
+        // Snapshot function - Plays 20 seconds weaving and shooting, then notes where everything is
+        let snapshot = |seed: u64| {
+            let mut world = new_world(seed);
+            world.lives = 1000;
+            for tick in 0..(TICK_RATE * 20.0) as usize {
+                let input = Input {
+                    left: tick / 240 % 2 == 0,
+                    right: tick / 240 % 2 == 1,
+                    fire: tick % 30 == 0,
+                    ..Default::default()
+                };
+                world.step(TIME_STEP, input);
+            }
+            let enemies: Vec<(f32, f32)> = world
+                .enemies
+                .iter()
+                .map(|enemy| (enemy.shape.x, enemy.shape.y))
+                .collect();
+            (world.score, world.lives, enemies)
+        };
+
+        let first = snapshot(7);
+        assert!(!first.2.is_empty());
+        assert_eq!(first, snapshot(7));
+        assert_ne!(first, snapshot(8));
+    }
+
+    #[test]
+    fn lives_are_lost_and_the_player_respawns() {
+        let mut world = new_world(1);
+        world.lives = 2;
+        world.weapon.level = 2;
+        world.active_power_ups.activate(PowerUpKind::RapidFire);
+        let player = vec2(world.player.x, world.player.y);
+        world.fire_bolt(player, Vec2::ZERO);
+
+        let events = world.step(TIME_STEP, Input::default());
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::PlayerHit { .. })));
+        assert_eq!(world.lives, 1);
+        assert_eq!(world.weapon.level, 1);
+        assert!(!world.active_power_ups.active(PowerUpKind::RapidFire));
+        assert!(!world.player_alive());
+        assert!(!world.game_over);
+
+        // Back after RESPAWN_TIME, invulnerable for a while so a bolt where they are does nothing
+        let mut respawned = false;
+        for _ in 0..(RESPAWN_TIME * TICK_RATE) as usize + 1 {
+            respawned |= world
+                .step(TIME_STEP, Input::default())
+                .iter()
+                .any(|event| matches!(event, GameEvent::PlayerRespawned { .. }));
+        }
+        assert!(respawned);
+        assert!(world.player_alive());
+        assert!(world.invulnerable_timer > 0.0);
+        let player = vec2(world.player.x, world.player.y);
+        world.fire_bolt(player, Vec2::ZERO);
+        world.step(TIME_STEP, Input::default());
+        assert_eq!(world.lives, 1);
+
+        // Losing the last life ends the run
+        world.invulnerable_timer = 0.0;
+        world.fire_bolt(player, Vec2::ZERO);
+        world.step(TIME_STEP, Input::default());
+        assert_eq!(world.lives, 0);
+        assert!(world.game_over);
+    }
+
+    #[test]
+    fn boss_turrets_are_hit_before_the_shielded_core() {
+        let mut world = new_world(1);
+        world.level = BOSS_LEVEL_INTERVAL;
+        world.invulnerable_timer = 100.0;
+        for _ in 0..(TICK_RATE * 30.0) as usize {
+            world.step(TIME_STEP, Input::default());
+            if world.boss.as_ref().unwrap().phase != BossPhase::Entering {
+                break;
+            }
+        }
+
+        // Shoot function - Fires one bullet and holds it still at offset from the core for a step, made
+        // bigger so it can reach across from a turret to the core. Optionally the left turret is destroyed
+        // just before, as if by another bullet in the same step
+        let shoot = |world: &mut World, offset: Vec2, destroy_turret: bool| {
+            world.fire_cooldown = 0.0;
+            world.step(
+                TIME_STEP,
+                Input {
+                    fire: true,
+                    ..Default::default()
+                },
+            );
+            let core = &world.boss.as_ref().unwrap().parts[0].shape;
+            let position = vec2(core.x, core.y) + offset;
+            let bullet = world.bullets.last_mut().unwrap();
+            (bullet.shape.x, bullet.shape.y) = (position.x, position.y);
+            bullet.velocity = Vec2::ZERO;
+            bullet.shape.size = 64.0;
+            if destroy_turret {
+                world.boss.as_mut().unwrap().parts[1].hp = 0;
+            }
+            world.step(TIME_STEP, Input::default()).to_vec()
+        };
+        let hp = |world: &World| -> Vec<u32> {
+            world
+                .boss
+                .as_ref()
+                .unwrap()
+                .parts
+                .iter()
+                .map(|part| part.hp)
+                .collect()
+        };
+
+        // Between the core and the left turret
+        let before = hp(&world);
+        let events = shoot(&mut world, vec2(-60.0, 8.0), false);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::EnemyHit { .. })));
+        assert_eq!(hp(&world), [before[0], before[1] - 1, before[2]]);
+
+        // Only the core, which bounces it off
+        let before = hp(&world);
+        let events = shoot(&mut world, vec2(0.0, -40.0), false);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::ShotDeflected { .. })));
+        assert_eq!(hp(&world), before);
+        assert!(world.bullets.iter().all(|bullet| bullet.shape.collided));
+
+        // A turret destroyed earlier in the step doesn't soak up the bullet, it goes on to the core
+        let events = shoot(&mut world, vec2(-60.0, 8.0), true);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::ShotDeflected { .. })));
+    }
+
+    #[test]
+    fn spread_shot_counts_every_bullet() {
+        let mut world = new_world(1);
+        let fire = Input {
+            fire: true,
+            ..Default::default()
+        };
+        let shot = |events: &[GameEvent]| {
+            events.iter().find_map(|event| match event {
+                GameEvent::ShotFired { bullets, .. } => Some(*bullets),
+                _ => None,
+            })
+        };
+
+        assert_eq!(shot(world.step(TIME_STEP, fire)), Some(1));
+        world.active_power_ups.activate(PowerUpKind::SpreadShot);
+        world.fire_cooldown = 0.0;
+        assert_eq!(shot(world.step(TIME_STEP, fire)), Some(3));
+        assert_eq!(world.bullets.len(), 4);
+
+        // Every bullet of a multi-projectile weapon counts too, three volleys of them with spread shot
+        world.weapon = Weapon {
+            kind: WeaponKind::Laser,
+            level: 3,
+        };
+        world.fire_cooldown = 0.0;
+        assert_eq!(shot(world.step(TIME_STEP, fire)), Some(9));
+    }
+
+    #[test]
+    fn formation_dive_returns_to_its_slot() {
+        let mut world = new_world(1);
+        world.lives = 1000;
+        let slot = Slot { column: 3, row: 1 };
+        let enemy = world.spawn_enemy(EnemyKind::Weaver, Movement::Weave, None);
+        enemy.slot = Some(slot);
+        enemy.flight = Some(Flight::Holding);
+        let target = vec2(world.player.x, world.player.y);
+        world.formation_dive(target);
+        assert!(matches!(world.enemies[0].flight, Some(Flight::Path { .. })));
+
+        // Off the bottom, back in from the top and into the formation again
+        let mut went_below = false;
+        for _ in 0..(TICK_RATE * 20.0) as usize {
+            world.step(TIME_STEP, Input::default());
+            let enemy = world
+                .enemies
+                .iter()
+                .find(|enemy| enemy.slot == Some(slot))
+                .expect("the diving enemy was removed");
+            went_below |= enemy.shape.y > world.height;
+            if went_below && matches!(enemy.flight, Some(Flight::Holding)) {
+                return;
+            }
+        }
+        panic!("the diving enemy never got back to its slot");
+    }
+
+    #[test]
+    fn bigger_enemies_take_more_hits() {
+        let mut world = new_world(1);
+        world.invulnerable_timer = 100.0;
+        let enemy = world.spawn_enemy(EnemyKind::Strafer, Movement::Straight, None);
+        (enemy.shape.x, enemy.shape.y, enemy.shape.speed) = (200.0, 200.0, 0.0);
+        let size = enemy.shape.size;
+        assert_eq!(enemy.hp, SizeClass::Large.hit_points());
+
+        // Shoot function - Fires one bullet and holds it still on the enemy for a step
+        let shoot = |world: &mut World| {
+            world.fire_cooldown = 0.0;
+            world.step(
+                TIME_STEP,
+                Input {
+                    fire: true,
+                    ..Default::default()
+                },
+            );
+            let bullet = world.bullets.last_mut().unwrap();
+            (bullet.shape.x, bullet.shape.y) = (200.0, 200.0);
+            bullet.velocity = Vec2::ZERO;
+            world.step(TIME_STEP, Input::default()).to_vec()
+        };
+
+        // Every hit but the last flashes the enemy and scores nothing
+        for hp in (1..SizeClass::Large.hit_points()).rev() {
+            let events = shoot(&mut world);
+            assert!(events
+                .iter()
+                .any(|event| matches!(event, GameEvent::EnemyHit { .. })));
+            let enemy = &world.enemies[0];
+            assert_eq!(enemy.hp, hp);
+            assert!(enemy.hit_flash > 0.0);
+            assert_eq!(world.score, 0);
+        }
+
+        let events = shoot(&mut world);
+        assert!(events.contains(&GameEvent::EnemyDestroyed {
+            size,
+            pos: vec2(200.0, 200.0)
+        }));
+        assert_eq!(world.score, size.round() as u32);
+    }
+
+    #[test]
+    fn enemy_kinds_move_their_own_way() {
+        let mut world = new_world(1);
+        // Fly function - Spawns an enemy heading left and moves it on its own for seconds, noting where it was
+        // each step
+        let mut fly = |kind: EnemyKind, x: f32, target: Vec2, seconds: f32| {
+            world.spawn_enemy(kind, kind.movement(), Some(x));
+            let mut enemy = world.enemies.pop().unwrap();
+            enemy.direction = -1.0;
+            let mut positions = vec![];
+            for _ in 0..(seconds * TICK_RATE) as usize {
+                enemy.update(TIME_STEP, Some(target), 800.0, 600.0, &world.formation);
+                positions.push(vec2(enemy.shape.x, enemy.shape.y));
+            }
+            (enemy, positions)
+        };
+
+        // Weavers swing both ways around where they spawned
+        let (weaver, positions) = fly(EnemyKind::Weaver, 0.5, Vec2::ZERO, 3.0);
+        let offsets = positions
+            .iter()
+            .map(|position| position.x - weaver.origin_x);
+        let (left, right) = offsets.fold((0.0f32, 0.0f32), |(left, right), offset| {
+            (left.min(offset), right.max(offset))
+        });
+        assert!((-WEAVE_AMPLITUDE - 0.001..-WEAVE_AMPLITUDE * 0.9).contains(&left));
+        assert!((WEAVE_AMPLITUDE * 0.9..WEAVE_AMPLITUDE + 0.001).contains(&right));
+
+        // Divers go sideways as they fall and bounce off the sides
+        let (diver, positions) = fly(EnemyKind::Diver, 0.1, Vec2::ZERO, 10.0);
+        let half_size = diver.shape.size / 2.0;
+        assert!(positions
+            .iter()
+            .all(|position| (half_size..=800.0 - half_size).contains(&position.x)));
+        assert!(positions.windows(2).all(|pair| pair[1].y > pair[0].y));
+        assert!(positions.windows(3).any(|steps| {
+            (steps[1].x - steps[0].x).signum() != (steps[2].x - steps[1].x).signum()
+        }));
+
+        // Chasers steer across to the player
+        let (chaser, _) = fly(EnemyKind::Chaser, 0.75, vec2(700.0, 500.0), 10.0);
+        assert_eq!(chaser.shape.x, 700.0);
+
+        // Strafers stop near the top, strafe for a while, then carry on down
+        let (strafer, positions) = fly(EnemyKind::Strafer, 0.5, Vec2::ZERO, 10.0);
+        let stopped = positions
+            .windows(2)
+            .filter(|pair| pair[1].y == pair[0].y && pair[1].x != pair[0].x)
+            .count();
+        assert_eq!(stopped, (STRAFE_TIME * TICK_RATE).round() as usize);
+        assert!(strafer.shape.y > strafer.stop_y);
+
+        for movement in Movement::ALL {
+            assert_eq!(Movement::from_id(movement.id()), Some(movement));
+        }
+        for kind in EnemyKind::ALL {
+            assert_eq!(EnemyKind::from_id(kind.id()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn enemies_fire_at_the_player() {
+        let mut world = new_world(1);
+        let target = vec2(500.0, 600.0);
+        let mut spawn = |kind: EnemyKind| {
+            let enemy = world.spawn_enemy(kind, Movement::Straight, None);
+            (enemy.shape.x, enemy.shape.y, enemy.fire_timer) = (200.0, 200.0, 0.0);
+            world.enemies.pop().unwrap()
+        };
+
+        // Aimed bolts head for the player, then the enemy waits for its weapon
+        let mut strafer = spawn(EnemyKind::Strafer);
+        let velocity = strafer.fire(TIME_STEP, Some(target)).unwrap();
+        let aim = (target - vec2(200.0, 200.0)).normalize() * ENEMY_BULLET_SPEED;
+        assert!(velocity.distance(aim) < 0.001);
+        assert_eq!(strafer.fire(TIME_STEP, Some(target)), None);
+        let (_, interval) = EnemyKind::Strafer.weapon().unwrap();
+        let shots = (0..(interval * TICK_RATE) as usize)
+            .filter(|_| strafer.fire(TIME_STEP, Some(target)).is_some())
+            .count();
+        assert_eq!(shots, 1);
+
+        // Nothing to shoot at while the player is respawning, or from above the play area
+        let mut diver = spawn(EnemyKind::Diver);
+        assert_eq!(diver.fire(TIME_STEP, None), None);
+        diver.shape.y = -10.0;
+        assert_eq!(diver.fire(TIME_STEP, Some(target)), None);
+        diver.shape.y = 200.0;
+        assert_eq!(
+            diver.fire(TIME_STEP, Some(target)),
+            Some(vec2(0.0, ENEMY_BULLET_SPEED))
+        );
+
+        let mut weaver = spawn(EnemyKind::Weaver);
+        assert_eq!(weaver.fire(TIME_STEP, Some(target)), None);
+    }
+
+    #[test]
+    fn enemy_bolts_pass_through_enemies() {
+        let mut world = new_world(1);
+        let enemy = world.spawn_enemy(EnemyKind::Weaver, Movement::Straight, None);
+        (enemy.shape.x, enemy.shape.y, enemy.shape.speed) = (200.0, 200.0, 0.0);
+        world.fire_bolt(vec2(200.0, 200.0), Vec2::ZERO);
+        world.step(TIME_STEP, Input::default());
+
+        let enemy = &world.enemies[0];
+        assert_eq!(enemy.hp, SizeClass::Small.hit_points());
+        assert!(!enemy.shape.collided);
+        assert!(!world.enemy_bullets[0].shape.collided);
+    }
+
+    #[test]
+    fn shield_pickup_soaks_up_hits_until_it_runs_out() {
+        let mut world = new_world(1);
+        let player = vec2(world.player.x, world.player.y);
+        world
+            .power_ups
+            .push(PowerUp::new(Pickup::PowerUp(PowerUpKind::Shield), player));
+        let events = world.step(TIME_STEP, Input::default());
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::PowerUpCollected {
+                kind: PowerUpKind::Shield,
+                ..
+            }
+        )));
+        assert!(world.power_ups.is_empty());
+
+        world.fire_bolt(player, Vec2::ZERO);
+        world.step(TIME_STEP, Input::default());
+        assert_eq!(world.lives, STARTING_LIVES);
+        assert!(world
+            .enemy_bullets
+            .iter()
+            .all(|bullet| bullet.shape.collided));
+
+        // Once it runs out the next bolt costs a life
+        run(&mut world, PowerUpKind::Shield.duration(), Input::default());
+        assert!(!world.active_power_ups.active(PowerUpKind::Shield));
+        world.fire_bolt(player, Vec2::ZERO);
+        world.step(TIME_STEP, Input::default());
+        assert_eq!(world.lives, STARTING_LIVES - 1);
+    }
+
+    #[test]
+    fn weapon_pickups_upgrade_the_weapon() {
+        let mut world = new_world(1);
+        let player = vec2(world.player.x, world.player.y);
+        world
+            .power_ups
+            .push(PowerUp::new(Pickup::Weapon(WeaponKind::Laser), player));
+        let events = world.step(TIME_STEP, Input::default());
+        assert!(events.iter().any(|event| matches!(
+            event,
+            GameEvent::WeaponCollected {
+                kind: WeaponKind::Laser,
+                level: 2,
+                ..
+            }
+        )));
+
+        world
+            .power_ups
+            .push(PowerUp::new(Pickup::Weapon(WeaponKind::WaveBeam), player));
+        world.step(TIME_STEP, Input::default());
+        assert_eq!(
+            (world.weapon.kind, world.weapon.level),
+            (WeaponKind::WaveBeam, 2)
+        );
+    }
+}